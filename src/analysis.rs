@@ -1,15 +1,30 @@
 // analysis.rs -- move evaluation + eval meter for coaching
 
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::attack::{AttackConfig, QUAD};
+use crate::board::{Board, BOARD_HEIGHT, FULL_ROW, VISIBLE_HEIGHT};
 use crate::calibration::{
     default_eval_thresholds, BucketThresholds, CalibrationProfile, SkillBucket,
 };
-use crate::eval::{evaluate, EvalWeights};
-use crate::header::Move;
-use crate::search::{find_best_move_with_scores, SearchConfig};
-use crate::state::{CoachingState, FatalityState, GameState, ObligationState, SurgeState};
+use crate::eval::{count_holes, evaluate, newly_covered_holes, surface_bumpiness, EvalWeights};
+use crate::header::{Move, Piece, SpinType, COL_NB};
+use crate::move_buffer::MoveBuffer;
+use crate::movegen::{footprint, generate};
+use crate::pathfinder::is_finesse_fault;
+use crate::search::{find_best_move_with_scores, placement_attack, GameStats, SearchConfig};
+use crate::search_config::SearchResultFull;
+use crate::state::{
+    CoachingState, FatalityState, GameOverReason, GameState, ObligationState, SurgeState,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Severity {
+    #[default]
     None,
     Inaccuracy,
     Mistake,
@@ -77,7 +92,7 @@ pub fn classify_major_first(
     eval_bucket
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct MoveAnalysis {
     pub eval_before: f32,
     pub eval_after: f32,
@@ -206,6 +221,7 @@ pub enum InsightTag {
     AttackWindowMiss,
     ChainBreak,
     DownstackEfficiencyMiss,
+    InefficientClear,
 }
 
 impl InsightTag {
@@ -214,6 +230,19 @@ impl InsightTag {
             InsightTag::AttackWindowMiss => "attack_window_miss",
             InsightTag::ChainBreak => "chain_break",
             InsightTag::DownstackEfficiencyMiss => "downstack_efficiency_miss",
+            InsightTag::InefficientClear => "inefficient_clear",
+        }
+    }
+
+    /// Short coaching suggestion shown alongside the tag.
+    pub fn suggestion(self) -> &'static str {
+        match self {
+            InsightTag::AttackWindowMiss => "An attack was available here — look for the clear.",
+            InsightTag::ChainBreak => "Keep the combo going instead of breaking it.",
+            InsightTag::DownstackEfficiencyMiss => "A cleaner downstack was available.",
+            InsightTag::InefficientClear => {
+                "A stronger clear was available — save the well or chain for a Tetris or spin."
+            }
         }
     }
 }
@@ -252,6 +281,16 @@ pub fn assemble_composite(
         + context * config.context_weight
 }
 
+/// Topout risk of a node for `SearchConfig::survival_weight`: 0 when safe,
+/// 1 when critical, 2 when fatal (see `CoachingState::transition`).
+pub fn topout_risk(coaching: &CoachingState) -> f32 {
+    match coaching.fatality {
+        FatalityState::Safe => 0.0,
+        FatalityState::Critical => 1.0,
+        FatalityState::Fatal => 2.0,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InsightDetectorInput {
     pub best_attack_score: f32,
@@ -318,6 +357,137 @@ pub fn detect_insights(input: &InsightDetectorInput) -> Vec<InsightResult> {
     results
 }
 
+const TETRIS_WELL_DEPTH: usize = 4;
+
+/// Flags an I piece spent elsewhere while `well_column` held a Tetris-ready
+/// well (4 rows full except the well). Returns None for any other piece, when
+/// no such well existed, or when the I went vertically into the well. The
+/// moment's `frame` is 0; callers scanning a replay set it.
+pub fn detect_wasted_i(
+    board_before: &Board,
+    piece: Piece,
+    player_move: &Move,
+    well_column: usize,
+) -> Option<Moment> {
+    if piece != Piece::I || well_column >= COL_NB {
+        return None;
+    }
+
+    let well_col = board_before.cols[well_column];
+    let well_floor = (64 - well_col.leading_zeros()) as usize;
+    if well_floor + TETRIS_WELL_DEPTH > BOARD_HEIGHT {
+        return None;
+    }
+    let ready_row = FULL_ROW & !(1u16 << well_column);
+    let ready =
+        (well_floor..well_floor + TETRIS_WELL_DEPTH).all(|y| board_before.row(y) == ready_row);
+    if !ready {
+        return None;
+    }
+
+    let pc = player_move.cells();
+    let x = player_move.x();
+    let in_well = pc
+        .coords
+        .iter()
+        .all(|c| x + c.x as i32 == well_column as i32)
+        && x == well_column as i32;
+    if in_well {
+        return None;
+    }
+
+    Some(Moment {
+        frame: 0,
+        kind: MomentType::InefficientClear { well_column },
+        analysis: MoveAnalysis::default(),
+    })
+}
+
+/// Most holes a board may have for unevenness to count as a surface problem
+/// rather than a downstack one.
+const UNEVEN_MAX_HOLES: i32 = 2;
+
+/// Flags a mountainous surface (`surface_bumpiness` above `bumpiness_threshold`)
+/// on an otherwise clean board.
+pub fn detect_uneven_build(
+    board: &Board,
+    frame: u32,
+    bumpiness_threshold: u32,
+) -> Option<Moment> {
+    let bump = surface_bumpiness(board);
+    if bump <= bumpiness_threshold || count_holes(board) > UNEVEN_MAX_HOLES {
+        return None;
+    }
+    Some(Moment {
+        frame: frame as usize,
+        kind: MomentType::UnevenSurface {
+            excess: bump - bumpiness_threshold,
+        },
+        analysis: MoveAnalysis::default(),
+    })
+}
+
+/// Smallest chain whose loss on a plain clear is worth flagging.
+const B2B_WASTE_MIN: u8 = 4;
+
+/// B2B level after each frame's clear, `(lines_cleared, spin)` per frame.
+/// Non-clearing frames carry the level forward, matching search transitions.
+pub fn b2b_timeline(frames: &[(u8, SpinType)]) -> Vec<u8> {
+    let mut b2b = 0u8;
+    frames
+        .iter()
+        .map(|&(lines, spin)| {
+            if lines > 0 {
+                b2b = if spin != SpinType::NoSpin || lines >= 4 {
+                    b2b.saturating_add(1)
+                } else {
+                    0
+                };
+            }
+            b2b
+        })
+        .collect()
+}
+
+/// `detect_burned_b2b` at `B2B_WASTE_MIN`, reported as an InefficientClear
+/// insight. Severity grows with chain length.
+pub fn detect_b2b_break(b2b_before: u8, lines_cleared: u8, spin: SpinType) -> Option<InsightResult> {
+    detect_burned_b2b(b2b_before, lines_cleared, spin, 0, B2B_WASTE_MIN)?;
+    Some(InsightResult {
+        tag: InsightTag::InefficientClear,
+        severity: (b2b_before as f32 / 10.0).clamp(0.0, 1.0),
+        delta: b2b_before as f32,
+    })
+}
+
+/// Per-column height counts across `boards`: `hist[x][h]` is how many boards
+/// had column `x` at height `h`. Feeds the height heatmap.
+pub fn height_histogram(boards: &[Board]) -> [[u32; BOARD_HEIGHT + 1]; COL_NB] {
+    let mut hist = [[0u32; BOARD_HEIGHT + 1]; COL_NB];
+    for board in boards {
+        for (x, bins) in hist.iter_mut().enumerate() {
+            let h = (64 - board.cols[x].leading_zeros()) as usize;
+            bins[h.min(BOARD_HEIGHT)] += 1;
+        }
+    }
+    hist
+}
+
+// saturation points for `board_complexity`: a term maxes out here
+const COMPLEXITY_BUMP_CAP: f32 = 40.0;
+const COMPLEXITY_HOLE_CAP: f32 = 12.0;
+
+/// Board complexity on a 0-100 scale for rating replays and finding the
+/// hardest frames: 40% surface bumpiness (saturating at 40), 40% holes
+/// (saturating at 12) and 20% stack height as a fraction of the visible
+/// field. An empty board is 0.
+pub fn board_complexity(board: &Board) -> f32 {
+    let bump = (surface_bumpiness(board) as f32 / COMPLEXITY_BUMP_CAP).min(1.0);
+    let holes = (count_holes(board) as f32 / COMPLEXITY_HOLE_CAP).min(1.0);
+    let height = (board.height() as f32 / VISIBLE_HEIGHT as f32).min(1.0);
+    100.0 * (0.4 * bump + 0.4 * holes + 0.2 * height)
+}
+
 pub struct EvalMeter {
     weights: EvalWeights,
     search_config: SearchConfig,
@@ -328,7 +498,7 @@ pub struct EvalMeter {
 impl EvalMeter {
     pub fn new() -> Self {
         let weights = EvalWeights::default();
-        let baseline = evaluate(&crate::board::Board::new(), &weights);
+        let baseline = evaluate(&Board::new(), &weights);
         Self {
             weights,
             search_config: SearchConfig::default(),
@@ -338,7 +508,7 @@ impl EvalMeter {
     }
 
     pub fn with_config(weights: EvalWeights, config: SearchConfig) -> Self {
-        let baseline = evaluate(&crate::board::Board::new(), &weights);
+        let baseline = evaluate(&Board::new(), &weights);
         Self {
             weights,
             search_config: config,
@@ -360,6 +530,7 @@ impl EvalMeter {
             &self.weights,
             &self.search_config,
             default_eval_thresholds(),
+            None,
         );
         self.history.push(result.meter_value);
         result
@@ -387,6 +558,643 @@ impl Default for EvalMeter {
     }
 }
 
+/// One placement from a replay: the state before the piece locked and the
+/// move the player made with `state.current`.
+#[derive(Clone)]
+pub struct ReplayFrame {
+    pub state: GameState,
+    pub actual_move: Move,
+    /// Game frame the piece locked on, for timing (`pps_timeline`).
+    pub lock_frame: u32,
+    /// Inputs the player used for the piece, hard drop excluded; None when
+    /// the replay didn't record them (see `GameStats::finesse_faults`).
+    pub inputs: Option<usize>,
+}
+
+/// Pieces per second over a trailing window of `window_frames` game frames,
+/// sampled at each piece's `lock_frame` as `(lock_frame, pps)`. Frames must
+/// be in lock order. Samples start once a full window has elapsed, so the
+/// opening pieces don't read as slow; `fps` converts frames to seconds.
+pub fn pps_timeline(frames: &[ReplayFrame], window_frames: u32, fps: u32) -> Vec<(u32, f32)> {
+    if window_frames == 0 || fps == 0 {
+        return Vec::new();
+    }
+    let window_secs = window_frames as f32 / fps as f32;
+    let mut samples = Vec::new();
+    let mut first = 0;
+    for (i, frame) in frames.iter().enumerate() {
+        let now = frame.lock_frame;
+        // pieces locked in (now - window, now]
+        while now.saturating_sub(frames[first].lock_frame) >= window_frames {
+            first += 1;
+        }
+        if now >= window_frames {
+            samples.push((now, (i + 1 - first) as f32 / window_secs));
+        }
+    }
+    samples
+}
+
+/// Handle to a board stored in a `BoardInterner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoardId(u32);
+
+/// Stores each distinct board once, for replays where the same position
+/// recurs. Keyed on `content_hash`; colliding boards are told apart by
+/// `same_shape`, so ids never alias different boards.
+#[derive(Default)]
+pub struct BoardInterner {
+    boards: Vec<Board>,
+    by_hash: HashMap<u64, Vec<BoardId>>,
+}
+
+impl BoardInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id of `board`, storing a copy the first time it is seen.
+    pub fn intern(&mut self, board: &Board) -> BoardId {
+        let ids = self.by_hash.entry(board.content_hash()).or_default();
+        if let Some(&id) = ids
+            .iter()
+            .find(|id| self.boards[id.0 as usize].same_shape(board))
+        {
+            return id;
+        }
+        let id = BoardId(self.boards.len() as u32);
+        self.boards.push(board.clone());
+        ids.push(id);
+        id
+    }
+
+    /// Panics if `id` came from another interner and is out of range here.
+    pub fn resolve(&self, id: BoardId) -> &Board {
+        &self.boards[id.0 as usize]
+    }
+
+    /// Distinct boards stored.
+    pub fn len(&self) -> usize {
+        self.boards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boards.is_empty()
+    }
+}
+
+/// What made a frame worth a `Moment`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MomentType {
+    /// Search scored the move worse than `Severity::None`.
+    Misplay,
+    /// A plain clear spent a b2b chain of `b2b_before` (see `detect_burned_b2b`).
+    BurnedB2B { b2b_before: u8, lost_surge: f32 },
+    /// An S/Z left one hole that the adjacent `better` placement avoids
+    /// (see `detect_sz_parity_mistake`).
+    ParityHole { better: Move },
+    /// Surface bumpiness `excess` above the threshold on a clean board
+    /// (see `detect_uneven_build`).
+    UnevenSurface { excess: u32 },
+    /// An I went elsewhere while `well_column` was Tetris-ready
+    /// (see `detect_wasted_i`).
+    InefficientClear { well_column: usize },
+}
+
+/// A frame worth pointing out: a misplay, or a pattern a `detect_*` helper flagged.
+#[derive(Debug, Clone)]
+pub struct Moment {
+    pub frame: usize,
+    pub kind: MomentType,
+    /// Search analysis of the move; default (all zero) for `BurnedB2B`
+    /// moments built without one.
+    pub analysis: MoveAnalysis,
+}
+
+impl MomentType {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            MomentType::Misplay => "misplay",
+            MomentType::BurnedB2B { .. } => "burned_b2b",
+            MomentType::ParityHole { .. } => "parity_hole",
+            MomentType::UnevenSurface { .. } => "uneven_surface",
+            MomentType::InefficientClear { .. } => "inefficient_clear",
+        }
+    }
+}
+
+impl Moment {
+    /// One-line account of what went wrong, for exports and reports.
+    pub fn description(&self) -> String {
+        match self.kind {
+            MomentType::Misplay => format!(
+                "{:?}: lost {:.2} eval, engine preferred {:?} at x={} y={}",
+                self.analysis.severity,
+                self.analysis.eval_loss,
+                self.analysis.best_move.piece(),
+                self.analysis.best_move.x(),
+                self.analysis.best_move.y()
+            ),
+            MomentType::BurnedB2B {
+                b2b_before,
+                lost_surge,
+            } => format!(
+                "Plain clear broke a b2b chain of {b2b_before}, losing {lost_surge} surge"
+            ),
+            MomentType::ParityHole { better } => format!(
+                "{:?} left a hole, x={} y={} leaves none",
+                better.piece(),
+                better.x(),
+                better.y()
+            ),
+            MomentType::UnevenSurface { excess } => {
+                format!("Surface bumpiness {excess} over the limit on a clean board")
+            }
+            MomentType::InefficientClear { well_column } => {
+                format!("I placed outside the Tetris-ready well at x={well_column}")
+            }
+        }
+    }
+
+    /// Short coaching suggestion for the moment's type.
+    pub fn suggestion(&self) -> &'static str {
+        match self.kind {
+            MomentType::Misplay => "Compare with the engine's placement.",
+            MomentType::BurnedB2B { .. } => "Keep the chain alive with a Tetris or spin clear.",
+            MomentType::ParityHole { .. } => "Shift the S/Z a column to keep parity clean.",
+            MomentType::UnevenSurface { .. } => {
+                "The stack is getting mountainous — flatten it out before building up."
+            }
+            MomentType::InefficientClear { .. } => {
+                "Drop the I vertically into the well for the Tetris."
+            }
+        }
+    }
+
+    /// How much the move cost: the search-score gap to the engine's pick,
+    /// plus the lost surge for a burned chain, one per parity hole, the
+    /// bumpiness excess of an uneven surface, or the Tetris a wasted I gave up.
+    pub fn impact(&self) -> f32 {
+        match self.kind {
+            MomentType::Misplay => self.analysis.eval_loss,
+            MomentType::BurnedB2B { lost_surge, .. } => self.analysis.eval_loss + lost_surge,
+            MomentType::ParityHole { .. } => self.analysis.eval_loss + 1.0,
+            MomentType::UnevenSurface { excess } => self.analysis.eval_loss + excess as f32,
+            MomentType::InefficientClear { .. } => self.analysis.eval_loss + QUAD as f32,
+        }
+    }
+}
+
+/// Flags a clear that doesn't continue b2b (1-3 lines, no spin) made with a
+/// chain of at least `threshold`. `lost_surge` estimates the chain's surge as
+/// `calculate_attack_full` values it (4 at b2b 4, +1 per level after; 0 for
+/// shorter chains).
+pub fn detect_burned_b2b(
+    b2b_before: u8,
+    lines: u8,
+    spin: SpinType,
+    frame: u32,
+    threshold: u8,
+) -> Option<Moment> {
+    let continues_b2b = lines >= 4 || spin != SpinType::NoSpin;
+    if lines == 0 || continues_b2b || b2b_before < threshold {
+        return None;
+    }
+    let lost_surge = if b2b_before >= 4 { b2b_before as f32 } else { 0.0 };
+    Some(Moment {
+        frame: frame as usize,
+        kind: MomentType::BurnedB2B {
+            b2b_before,
+            lost_surge,
+        },
+        analysis: MoveAnalysis::default(),
+    })
+}
+
+/// Flags an S or Z placement that buries exactly one new hole when another
+/// placement of the same piece, shifted at most one column, buries none.
+/// The moment's `frame` is 0; callers scanning a replay set it.
+pub fn detect_sz_parity_mistake(
+    board_before: &Board,
+    piece: Piece,
+    player_move: &Move,
+) -> Option<Moment> {
+    if !matches!(piece, Piece::S | Piece::Z) {
+        return None;
+    }
+    let new_holes = |m: &Move| {
+        let mut after = board_before.clone();
+        after.do_move(m);
+        newly_covered_holes(board_before, &after)
+    };
+    if new_holes(player_move) != 1 {
+        return None;
+    }
+
+    let left = |m: &Move| footprint(m).iter().map(|c| c.0).min().unwrap_or(0);
+    let played_left = left(player_move);
+    let mut moves = MoveBuffer::new();
+    generate(board_before, &mut moves, piece, true);
+    let better = moves.iter().copied().find(|m| {
+        (left(m) - played_left).abs() <= 1
+            && board_before.legal_lock_placement(m)
+            && new_holes(m) == 0
+    })?;
+    Some(Moment {
+        frame: 0,
+        kind: MomentType::ParityHole { better },
+        analysis: MoveAnalysis::default(),
+    })
+}
+
+fn frame_lines(frame: &ReplayFrame) -> (Board, u8) {
+    let mut board = frame.state.board.clone();
+    let lines = board.do_move(&frame.actual_move) as u8;
+    (board, lines)
+}
+
+// folds one played frame into `stats`, attack valued under `attack_config`
+fn record_frame(
+    stats: &mut GameStats,
+    frame: &ReplayFrame,
+    board_after: &Board,
+    lines: u8,
+    attack_config: &AttackConfig,
+) {
+    let mv = &frame.actual_move;
+    let attack = placement_attack(&frame.state, mv, board_after, lines, attack_config);
+    let (b2b, combo) = GameState::next_chain_values(frame.state.b2b, frame.state.combo, mv, lines);
+    stats.record(lines, attack, combo, b2b);
+    stats.holes = count_holes(board_after);
+    // unrecorded inputs can't be judged; skip rather than guess
+    if let Some(used) = frame.inputs {
+        if is_finesse_fault(&frame.state.board, mv, used) {
+            stats.finesse_faults += 1;
+        }
+    }
+    // advance to the piece that spawns next: check_game_over block-outs
+    // test that one, not the piece just played
+    let mut after = GameState {
+        board: board_after.clone(),
+        ..frame.state.clone()
+    };
+    if mv.piece() != after.current {
+        if let Some(held) = after.with_hold_swap() {
+            after = held;
+        }
+    }
+    let over = match after.pop_next() {
+        Some(_) => after.check_game_over(mv),
+        // nothing left to spawn: only a lock out is known
+        None => after
+            .check_game_over(mv)
+            .filter(|reason| *reason == GameOverReason::LockOut),
+    };
+    stats.topped_out |= over.is_some();
+}
+
+/// Output of `analyze_replay`.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisResult {
+    pub moments: Vec<Moment>,
+    /// Engine's pick for every frame: (frame index, move, search score).
+    pub recommended: Vec<(u32, Move, f32)>,
+    /// Totals for the moves actually played.
+    pub stats: GameStats,
+}
+
+// quotes a CSV field when it holds a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// `result.moments` as CSV, one row per moment under a
+/// `frame,type,impact,description,suggestion` header.
+pub fn moments_to_csv(result: &AnalysisResult) -> String {
+    let mut csv = String::from("frame,type,impact,description,suggestion\n");
+    for moment in &result.moments {
+        csv.push_str(&format!(
+            "{},{},{:.2},{},{}\n",
+            moment.frame,
+            moment.kind.to_str(),
+            moment.impact(),
+            csv_field(&moment.description()),
+            csv_field(moment.suggestion())
+        ));
+    }
+    csv
+}
+
+/// Batch analysis of a whole replay: every frame becomes a `MoveAnalysis`,
+/// the ones with a non-`None` severity are returned as moments, and each
+/// frame's best move is recorded for side-by-side review. Plain clears that
+/// spend a b2b chain of `B2B_WASTE_MIN` or more are added as `BurnedB2B`
+/// moments, judged from each frame's `state.b2b`.
+pub fn analyze_replay(
+    frames: &[ReplayFrame],
+    weights: &EvalWeights,
+    config: &SearchConfig,
+) -> AnalysisResult {
+    let analyses = frames
+        .iter()
+        .map(|frame| analyze_frame(frame, weights, config))
+        .collect();
+    collect_replay(frames, analyses, config)
+}
+
+/// `analyze_replay` over `frames[start..end]` only, `stats` included. Frame
+/// indices in `moments` and `recommended` index into `frames`, not the
+/// window. Errors on an out-of-bounds or reversed range.
+pub fn analyze_replay_range(
+    frames: &[ReplayFrame],
+    start: usize,
+    end: usize,
+    weights: &EvalWeights,
+    config: &SearchConfig,
+) -> Result<AnalysisResult, String> {
+    if start > end || end > frames.len() {
+        return Err(format!(
+            "invalid replay range {start}..{end} for {} frames",
+            frames.len()
+        ));
+    }
+    let mut result = analyze_replay(&frames[start..end], weights, config);
+    for moment in &mut result.moments {
+        moment.frame += start;
+    }
+    for (frame, _, _) in &mut result.recommended {
+        *frame += start as u32;
+    }
+    Ok(result)
+}
+
+/// `analyze_replay` with the per-frame searches run in parallel when rayon
+/// is enabled, sharing an `EvalCache` so frames that revisit a position skip
+/// its movegen and eval. The result is identical to `analyze_replay`.
+pub fn analyze_replay_batch(
+    frames: &[ReplayFrame],
+    weights: &EvalWeights,
+    config: &SearchConfig,
+) -> AnalysisResult {
+    let cache = EvalCache::default();
+    #[cfg(feature = "rayon")]
+    let analyses = frames
+        .par_iter()
+        .map(|frame| analyze_frame_cached(frame, weights, config, &cache))
+        .collect();
+    #[cfg(not(feature = "rayon"))]
+    let analyses = frames
+        .iter()
+        .map(|frame| analyze_frame_cached(frame, weights, config, &cache))
+        .collect();
+    collect_replay(frames, analyses, config)
+}
+
+fn analyze_frame(
+    frame: &ReplayFrame,
+    weights: &EvalWeights,
+    config: &SearchConfig,
+) -> MoveAnalysis {
+    let (_, lines) = frame_lines(frame);
+    evaluate_move(&frame.state, &frame.actual_move, lines, weights, config)
+}
+
+fn analyze_frame_cached(
+    frame: &ReplayFrame,
+    weights: &EvalWeights,
+    config: &SearchConfig,
+    cache: &EvalCache,
+) -> MoveAnalysis {
+    let (_, lines) = frame_lines(frame);
+    analyze_move_inner(
+        &frame.state,
+        &frame.actual_move,
+        lines,
+        weights,
+        config,
+        default_eval_thresholds(),
+        Some(cache),
+    )
+}
+
+// search inputs besides the board; a cached search is reused only when these
+// match too
+#[derive(PartialEq)]
+struct SearchKey {
+    current: Piece,
+    hold: Option<Piece>,
+    queue: Vec<Piece>,
+    b2b: u8,
+    combo: u32,
+    pending_garbage: u8,
+    coaching: CoachingState,
+}
+
+impl SearchKey {
+    fn of(state: &GameState) -> Self {
+        Self {
+            current: state.current,
+            hold: state.hold,
+            queue: state.queue.clone(),
+            b2b: state.b2b,
+            combo: state.combo,
+            pending_garbage: state.pending_garbage,
+            coaching: state.coaching,
+        }
+    }
+}
+
+type CachedEvals = HashMap<u64, Vec<(Board, f32)>>;
+type CachedSearches = HashMap<u64, Vec<(Board, SearchKey, Option<SearchResultFull>)>>;
+
+// static evals and searches for one batch (one weights/config pair), keyed
+// by `Board::content_hash`; like `BoardInterner`, a hit must also be
+// `same_shape`, so colliding boards never share a result
+#[derive(Default)]
+struct EvalCache {
+    evals: Mutex<CachedEvals>,
+    searches: Mutex<CachedSearches>,
+}
+
+impl EvalCache {
+    fn evaluate(&self, board: &Board, weights: &EvalWeights) -> f32 {
+        let hash = board.content_hash();
+        let cached = self
+            .evals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&hash)
+            .and_then(|entries| entries.iter().find(|(b, _)| b.same_shape(board)))
+            .map(|&(_, score)| score);
+        cached.unwrap_or_else(|| {
+            let score = evaluate(board, weights);
+            self.evals
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(hash)
+                .or_default()
+                .push((board.clone(), score));
+            score
+        })
+    }
+
+    fn search(
+        &self,
+        state: &GameState,
+        config: &SearchConfig,
+        weights: &EvalWeights,
+    ) -> Option<SearchResultFull> {
+        let hash = state.board.content_hash();
+        let key = SearchKey::of(state);
+        {
+            let searches = self.searches.lock().unwrap_or_else(PoisonError::into_inner);
+            let hit = searches.get(&hash).and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|(b, k, _)| b.same_shape(&state.board) && *k == key)
+            });
+            if let Some((_, _, result)) = hit {
+                return result.clone();
+            }
+        }
+        // searched outside the lock; a racing frame may search the same
+        // position, which only costs time
+        let result = find_best_move_with_scores(state, config, weights);
+        self.searches
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(hash)
+            .or_default()
+            .push((state.board.clone(), key, result.clone()));
+        result
+    }
+}
+
+// folds per-frame analyses (in frame order) into moments, picks and stats
+fn collect_replay(
+    frames: &[ReplayFrame],
+    analyses: Vec<MoveAnalysis>,
+    config: &SearchConfig,
+) -> AnalysisResult {
+    let mut result = AnalysisResult::default();
+    for (i, (frame, analysis)) in frames.iter().zip(analyses).enumerate() {
+        let (board_after, lines) = frame_lines(frame);
+        record_frame(
+            &mut result.stats,
+            frame,
+            &board_after,
+            lines,
+            &config.attack_config,
+        );
+        result
+            .recommended
+            .push((i as u32, analysis.best_move, analysis.best_eval));
+        if analysis.severity != Severity::None {
+            result.moments.push(Moment {
+                frame: i,
+                kind: MomentType::Misplay,
+                analysis,
+            });
+        }
+        result.moments.extend(burned_b2b_moment(frame, lines, i));
+    }
+    result
+}
+
+// BurnedB2B moment for frame `index` if its clear spent the chain
+fn burned_b2b_moment(frame: &ReplayFrame, lines: u8, index: usize) -> Option<Moment> {
+    let spin = frame.actual_move.spin();
+    detect_burned_b2b(frame.state.b2b, lines, spin, index as u32, B2B_WASTE_MIN)
+}
+
+/// `analyze_replay` under a specific game mode: both the search and the
+/// attack totals in `stats` use `attack_config` in place of
+/// `config.attack_config`, so PC and surge values match the mode played.
+pub fn analyze_replay_with_config(
+    frames: &[ReplayFrame],
+    weights: &EvalWeights,
+    config: &SearchConfig,
+    attack_config: &AttackConfig,
+) -> AnalysisResult {
+    let config = SearchConfig {
+        attack_config: attack_config.clone(),
+        ..config.clone()
+    };
+    analyze_replay(frames, weights, &config)
+}
+
+/// The `n` highest-impact moments for sharing, earliest frame first on ties.
+pub fn highlights(result: &AnalysisResult, n: usize) -> Vec<Moment> {
+    let mut ranked: Vec<&Moment> = result.moments.iter().collect();
+    ranked.sort_by(|a, b| {
+        b.impact()
+            .total_cmp(&a.impact())
+            .then_with(|| a.frame.cmp(&b.frame))
+    });
+    ranked.into_iter().take(n).cloned().collect()
+}
+
+/// Incremental counterpart of `analyze_replay` for live coaching: frames are
+/// pushed as they happen and running `GameStats` are kept alongside the meter.
+pub struct LiveAnalyzer {
+    meter: EvalMeter,
+    search_config: SearchConfig,
+    frames: usize,
+    stats: GameStats,
+}
+
+impl LiveAnalyzer {
+    pub fn new(weights: EvalWeights, config: SearchConfig) -> Self {
+        Self {
+            meter: EvalMeter::with_config(weights, config.clone()),
+            search_config: config,
+            frames: 0,
+            stats: GameStats::default(),
+        }
+    }
+
+    /// Analyses one frame and returns the moments it produced: a misplay
+    /// and/or a burned b2b chain, as in `analyze_replay`.
+    pub fn push_frame(&mut self, frame: ReplayFrame) -> Vec<Moment> {
+        let (board_after, lines) = frame_lines(&frame);
+        let analysis = self
+            .meter
+            .analyze_move(&frame.state, &frame.actual_move, lines);
+        record_frame(
+            &mut self.stats,
+            &frame,
+            &board_after,
+            lines,
+            &self.search_config.attack_config,
+        );
+
+        let index = self.frames;
+        self.frames += 1;
+        let mut moments = Vec::new();
+        if analysis.severity != Severity::None {
+            moments.push(Moment {
+                frame: index,
+                kind: MomentType::Misplay,
+                analysis,
+            });
+        }
+        moments.extend(burned_b2b_moment(&frame, lines, index));
+        moments
+    }
+
+    pub fn stats(&self) -> &GameStats {
+        &self.stats
+    }
+
+    pub fn meter(&self) -> &EvalMeter {
+        &self.meter
+    }
+}
+
 fn analyze_move_inner(
     state: &GameState,
     actual_move: &Move,
@@ -394,13 +1202,18 @@ fn analyze_move_inner(
     weights: &EvalWeights,
     config: &SearchConfig,
     thresholds: BucketThresholds,
+    cache: Option<&EvalCache>,
 ) -> MoveAnalysis {
-    let eval_before = evaluate(&state.board, weights);
+    let eval = |board: &Board| match cache {
+        Some(cache) => cache.evaluate(board, weights),
+        None => evaluate(board, weights),
+    };
+    let eval_before = eval(&state.board);
 
     let mut result_board = state.board.clone();
     result_board.do_move(actual_move);
 
-    let eval_after = evaluate(&result_board, weights);
+    let eval_after = eval(&result_board);
     let inferred_hold_used = state.infer_hold_used_for_piece(actual_move.piece());
     let spawn_envelope_blocked = GameState::spawn_envelope_blocked(&result_board);
     let coaching_before = state.coaching;
@@ -412,7 +1225,10 @@ fn analyze_move_inner(
         spawn_envelope_blocked,
     );
 
-    let search_result = find_best_move_with_scores(state, config, weights);
+    let search_result = match cache {
+        Some(cache) => cache.search(state, config, weights),
+        None => find_best_move_with_scores(state, config, weights),
+    };
 
     let (best_eval, best_move, best_hold_used, best_coaching_state, eval_loss, severity) =
         match search_result {
@@ -511,6 +1327,7 @@ pub fn evaluate_move(
         weights,
         config,
         default_eval_thresholds(),
+        None,
     )
 }
 
@@ -533,18 +1350,18 @@ pub fn evaluate_move_for_bucket(
         weights,
         config,
         thresholds,
+        None,
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::Board;
     use crate::calibration::{
         generate_profile_from_players_manifest, CalibrationProfile, CALIBRATION_VERSION_V1,
     };
-    use crate::header::Piece;
-    use crate::movegen::{generate, MoveBuffer};
+    use crate::header::Rotation;
+    use crate::movegen::hard_drop;
     use crate::search::find_best_move;
     use crate::state::{PhaseState, SurgeState};
 
@@ -964,4 +1781,681 @@ mod tests {
         let sev = classify_win_prob_drop(best, actual, SIGMOID_K, c);
         assert_eq!(sev, Severity::Blunder); // massive WP drop crossing inflection
     }
+
+    fn tetris_ready_board() -> Board {
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[..4].fill(FULL_ROW & !(1 << 9));
+        Board::from(&rows)
+    }
+
+    fn i_moves(board: &Board) -> Vec<Move> {
+        let mut moves = MoveBuffer::new();
+        generate(board, &mut moves, Piece::I, false);
+        moves.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_detect_wasted_i_flags_flat_placement() {
+        let board = tetris_ready_board();
+        let flat = i_moves(&board)
+            .into_iter()
+            .find(|m| m.cells().coords.iter().all(|c| c.y == 0))
+            .unwrap_or_else(|| panic!("expected a flat I placement"));
+
+        let moment = detect_wasted_i(&board, Piece::I, &flat, 9)
+            .unwrap_or_else(|| panic!("flat I on a Tetris-ready well should be flagged"));
+        assert_eq!(moment.kind, MomentType::InefficientClear { well_column: 9 });
+        assert_eq!(moment.impact(), QUAD as f32);
+        assert!(!moment.suggestion().is_empty());
+    }
+
+    #[test]
+    fn test_detect_wasted_i_accepts_vertical_drop() {
+        let board = tetris_ready_board();
+        let vertical = i_moves(&board)
+            .into_iter()
+            .find(|m| m.x() == 9 && m.cells().coords.iter().all(|c| c.x == 0))
+            .unwrap_or_else(|| panic!("expected a vertical I into the well"));
+        assert!(detect_wasted_i(&board, Piece::I, &vertical, 9).is_none());
+
+        // non-I pieces and unready wells never fire
+        assert!(detect_wasted_i(&board, Piece::T, &vertical, 9).is_none());
+        assert!(detect_wasted_i(&Board::new(), Piece::I, &vertical, 9).is_none());
+    }
+
+    #[test]
+    fn test_height_histogram_bins() {
+        let flat = tetris_ready_board(); // x=0..8 at 4, x=9 at 0
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[6] = 1 << 2;
+        let tall = Board::from(&rows);
+
+        let hist = height_histogram(&[flat, tall]);
+        assert_eq!(hist[0][4], 1);
+        assert_eq!(hist[0][0], 1);
+        assert_eq!(hist[2][4], 1);
+        assert_eq!(hist[2][7], 1);
+        assert_eq!(hist[9][0], 2);
+        for bins in &hist {
+            assert_eq!(bins.iter().sum::<u32>(), 2);
+        }
+    }
+
+    #[test]
+    fn test_analysis_best_move_can_be_hold_swap() {
+        // S in hand, I in hold, Tetris-ready well at x=9
+        let mut state = GameState::new(tetris_ready_board(), Piece::S, vec![Piece::O]);
+        state.hold = Some(Piece::I);
+        let config = SearchConfig {
+            beam_width: 200,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+
+        let mut moves = MoveBuffer::new();
+        generate(&state.board, &mut moves, Piece::S, false);
+        let s_move = moves.as_slice()[0];
+        let mut after = state.board.clone();
+        let lines = after.do_move(&s_move) as u8;
+
+        let analysis = evaluate_move(&state, &s_move, lines, &weights, &config);
+        assert!(analysis.best_hold_used);
+        assert_eq!(analysis.best_move.piece(), Piece::I);
+
+        let full = find_best_move_with_scores(&state, &config, &weights)
+            .unwrap_or_else(|| panic!("search should find a move"));
+        for (m, score) in &full.root_scores {
+            if m.piece() == Piece::S {
+                assert!(*score < analysis.best_eval, "{m:?} scored {score}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_b2b_timeline_and_break_flag() {
+        let mut frames = vec![(4, SpinType::NoSpin); 3];
+        frames.push((0, SpinType::NoSpin));
+        frames.push((2, SpinType::Full));
+        frames.push((4, SpinType::NoSpin));
+        frames.push((1, SpinType::NoSpin));
+
+        let timeline = b2b_timeline(&frames);
+        assert_eq!(timeline, vec![1, 2, 3, 3, 4, 5, 0]);
+        assert_eq!(timeline.iter().copied().max(), Some(5));
+
+        let breaking = timeline.len() - 1;
+        let insight = detect_b2b_break(timeline[breaking - 1], 1, SpinType::NoSpin)
+            .unwrap_or_else(|| panic!("5-chain thrown on a single should be flagged"));
+        assert_eq!(insight.tag, InsightTag::InefficientClear);
+        assert_eq!(insight.delta, 5.0);
+
+        // short chains, spins and quads are not flagged
+        assert!(detect_b2b_break(3, 1, SpinType::NoSpin).is_none());
+        assert!(detect_b2b_break(6, 2, SpinType::Mini).is_none());
+        assert!(detect_b2b_break(6, 4, SpinType::NoSpin).is_none());
+        assert!(detect_b2b_break(6, 0, SpinType::NoSpin).is_none());
+    }
+
+    #[test]
+    fn test_analyze_replay_flags_burned_b2b_chain() {
+        // 20 rows open at x=9 for five quads, then a row open at x=0
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = FULL_ROW & !1;
+        rows[1..21].fill(FULL_ROW & !(1 << 9));
+        let mut state = GameState::new(Board::from(&rows), Piece::I, vec![Piece::I; 6]);
+        let mut frames = Vec::new();
+        for well in [9, 9, 9, 9, 9, 0] {
+            let mv = (0..COL_NB as i32)
+                .filter_map(|x| hard_drop(&state.board, Piece::I, Rotation::East, x))
+                .find(|m| footprint(m).iter().all(|c| c.0 == well))
+                .unwrap_or_else(|| panic!("vertical I into x={well}"));
+            frames.push(ReplayFrame {
+                state: state.clone(),
+                actual_move: mv,
+                lock_frame: 0,
+                inputs: None,
+            });
+            let mut board = state.board.clone();
+            let lines = board.do_move(&mv) as u8;
+            let blocked = GameState::spawn_envelope_blocked(&board);
+            state.apply_move_transition(&mv, lines, false, board.height(), blocked);
+            state.board = board;
+            state.pop_next();
+        }
+        assert_eq!(frames[5].state.b2b, 5);
+
+        let config = SearchConfig {
+            beam_width: 20,
+            depth: 1,
+            ..SearchConfig::default()
+        };
+        let result = analyze_replay(&frames, &EvalWeights::default(), &config);
+        assert_eq!(result.stats.max_b2b, 5);
+        assert_eq!(result.stats.singles, 1);
+        let burned: Vec<_> = result
+            .moments
+            .iter()
+            .filter_map(|m| match m.kind {
+                MomentType::BurnedB2B { b2b_before, .. } => Some((m.frame, b2b_before)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(burned, vec![(5, 5)]);
+    }
+
+    fn column_zero_frames(count: usize) -> Vec<ReplayFrame> {
+        let pieces = [Piece::T, Piece::S, Piece::Z, Piece::O, Piece::I, Piece::L, Piece::J];
+        let mut state = GameState::new(Board::new(), pieces[0], pieces[1..].to_vec());
+        let mut frames = Vec::new();
+        for _ in 0..count {
+            let mv = (0..COL_NB as i32)
+                .find_map(|x| hard_drop(&state.board, state.current, Rotation::North, x))
+                .unwrap_or_else(|| panic!("no drop for {:?}", state.current));
+            frames.push(ReplayFrame {
+                state: state.clone(),
+                actual_move: mv,
+                lock_frame: 0,
+                inputs: None,
+            });
+            let mut board = state.board.clone();
+            let lines = board.do_move(&mv) as u8;
+            let blocked = GameState::spawn_envelope_blocked(&board);
+            state.apply_move_transition(&mv, lines, false, board.height(), blocked);
+            state.board = board;
+            let next = state.current;
+            state.pop_next();
+            state.queue.push(next);
+        }
+        frames
+    }
+
+    #[test]
+    fn test_live_analyzer_matches_batch_replay() {
+        let config = SearchConfig {
+            beam_width: 40,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        let frames = column_zero_frames(7);
+
+        let batch = analyze_replay(&frames, &weights, &config).moments;
+        assert!(!batch.is_empty(), "leftmost drops should produce moments");
+
+        let mut live = LiveAnalyzer::new(weights, config);
+        let mut streamed = Vec::new();
+        for frame in frames.iter().cloned() {
+            streamed.extend(live.push_frame(frame));
+        }
+
+        let key = |m: &Moment| (m.frame, m.analysis.severity, m.analysis.best_move.raw());
+        assert_eq!(
+            streamed.iter().map(key).collect::<Vec<_>>(),
+            batch.iter().map(key).collect::<Vec<_>>()
+        );
+        assert_eq!(live.stats().pieces, frames.len());
+        assert_eq!(live.meter().history().len(), frames.len());
+        assert!(!live.stats().topped_out);
+    }
+
+    #[test]
+    fn test_analyze_replay_batch_matches_sequential() {
+        let config = SearchConfig {
+            beam_width: 40,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        // the replay revisits its first four positions, so the batch reuses
+        // their cached searches
+        let mut frames = column_zero_frames(6);
+        frames.extend(column_zero_frames(4));
+        let sequential = analyze_replay(&frames, &weights, &config);
+        let batch = analyze_replay_batch(&frames, &weights, &config);
+
+        let key = |m: &Moment| {
+            (
+                m.frame,
+                m.analysis.severity,
+                m.analysis.eval_loss,
+                m.analysis.best_move.raw(),
+            )
+        };
+        assert!(!batch.moments.is_empty());
+        assert_eq!(
+            batch.moments.iter().map(key).collect::<Vec<_>>(),
+            sequential.moments.iter().map(key).collect::<Vec<_>>()
+        );
+        let picks = |r: &AnalysisResult| {
+            r.recommended
+                .iter()
+                .map(|&(i, m, score)| (i, m.raw(), score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(picks(&batch), picks(&sequential));
+        assert_eq!(batch.stats, sequential.stats);
+    }
+
+    #[test]
+    fn test_analyze_replay_range_matches_slice() {
+        let config = SearchConfig {
+            beam_width: 40,
+            depth: 1,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        let frames = column_zero_frames(6);
+
+        let range = analyze_replay_range(&frames, 2, 5, &weights, &config)
+            .unwrap_or_else(|e| panic!("{e}"));
+        let slice = analyze_replay(&frames[2..5], &weights, &config);
+        assert_eq!(range.stats, slice.stats);
+        assert_eq!(range.stats.pieces, 3);
+        // indices map back to `frames`: the slice's 0 is frame 2
+        let picks = |r: &AnalysisResult, offset: u32| {
+            r.recommended
+                .iter()
+                .map(|&(i, m, score)| (i + offset, m.raw(), score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(picks(&range, 0), picks(&slice, 2));
+        assert_eq!(range.recommended[0].0, 2);
+        let frames_of = |r: &AnalysisResult, offset: usize| {
+            r.moments.iter().map(|m| m.frame + offset).collect::<Vec<_>>()
+        };
+        assert_eq!(frames_of(&range, 0), frames_of(&slice, 2));
+
+        assert!(analyze_replay_range(&frames, 4, 2, &weights, &config).is_err());
+        assert!(analyze_replay_range(&frames, 0, 7, &weights, &config).is_err());
+        let empty = analyze_replay_range(&frames, 6, 6, &weights, &config)
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(empty.stats.pieces, 0);
+    }
+
+    #[test]
+    fn test_eval_cache_searches_each_position_once() {
+        let config = SearchConfig {
+            beam_width: 20,
+            depth: 1,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        let mut frames = column_zero_frames(3);
+        frames.extend(column_zero_frames(3));
+        // same board, different preview: a separate search
+        let mut other_queue = frames[0].clone();
+        other_queue.state.queue.reverse();
+        frames.push(other_queue);
+
+        let cache = EvalCache::default();
+        for frame in &frames {
+            analyze_frame_cached(frame, &weights, &config, &cache);
+        }
+        let searches = cache.searches.lock().unwrap_or_else(PoisonError::into_inner);
+        assert_eq!(searches.len(), 3);
+        assert_eq!(searches.values().map(Vec::len).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_eval_cache_tells_colliding_boards_apart() {
+        let config = SearchConfig {
+            beam_width: 20,
+            depth: 1,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = FULL_ROW & !1;
+        rows[1] = 0b11;
+        let board = Board::from(&rows);
+        let mut other = Board::new();
+        other.fill_column(0, 1);
+        let state = GameState::new(board.clone(), Piece::T, vec![Piece::I]);
+
+        // plant `other`'s results under `board`'s hash, as a collision would
+        let cache = EvalCache::default();
+        let hash = board.content_hash();
+        cache
+            .evals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(hash, vec![(other.clone(), f32::MAX)]);
+        cache
+            .searches
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(hash, vec![(other, SearchKey::of(&state), None)]);
+
+        assert_eq!(cache.evaluate(&board, &weights), evaluate(&board, &weights));
+        assert!(cache.search(&state, &config, &weights).is_some());
+    }
+
+    #[test]
+    fn test_analyze_replay_recommends_one_legal_move_per_frame() {
+        let config = SearchConfig {
+            beam_width: 40,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        let frames = column_zero_frames(1);
+        let result = analyze_replay(&frames, &EvalWeights::default(), &config);
+
+        assert_eq!(result.recommended.len(), 1);
+        let (frame, mv, _) = result.recommended[0];
+        assert_eq!(frame, 0);
+        let board = &frames[0].state.board;
+        assert!(board.legal_lock_placement(&mv));
+        let mut moves = MoveBuffer::new();
+        generate(board, &mut moves, mv.piece(), true);
+        assert!(moves.iter().any(|g| g.raw() == mv.raw()));
+    }
+
+    #[test]
+    fn test_analyze_replay_with_config_values_pc_by_mode() {
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = FULL_ROW & !0xF;
+        let state = GameState::new(Board::from(&rows), Piece::I, vec![Piece::O, Piece::T]);
+        let actual_move = (0..COL_NB as i32)
+            .filter_map(|x| hard_drop(&state.board, Piece::I, Rotation::North, x))
+            .find(|m| state.board.clone().do_move(m) == 1)
+            .unwrap_or_else(|| panic!("flat I should fill the row"));
+        let frames = [ReplayFrame {
+            state,
+            actual_move,
+            lock_frame: 0,
+            inputs: None,
+        }];
+        let config = SearchConfig {
+            beam_width: 40,
+            depth: 1,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+
+        let league = analyze_replay_with_config(
+            &frames,
+            &weights,
+            &config,
+            &AttackConfig::tetra_league(),
+        );
+        let quick = analyze_replay_with_config(
+            &frames,
+            &weights,
+            &config,
+            &AttackConfig::quick_play(),
+        );
+        assert_eq!(league.stats.pieces, 1);
+        assert_eq!(league.stats.lines, 1);
+        // perfect-clear single: league pays 5 for the PC, quick play 3
+        assert!(quick.stats.attack > 0.0);
+        assert!(league.stats.attack > quick.stats.attack);
+        assert_eq!(league.recommended.len(), quick.recommended.len());
+    }
+
+    #[test]
+    fn test_record_frame_counts_finesse_faults() {
+        // T against the left wall is one DAS; tapping there takes three
+        let state = GameState::new(Board::new(), Piece::T, vec![Piece::O]);
+        let wall = Move::new(Piece::T, Rotation::North, 1, 0, false);
+        let mut board_after = state.board.clone();
+        board_after.do_move(&wall);
+        let frame = |inputs| ReplayFrame {
+            state: state.clone(),
+            actual_move: wall,
+            lock_frame: 0,
+            inputs,
+        };
+        let faults = |inputs| {
+            let mut stats = GameStats::default();
+            let config = AttackConfig::tetra_league();
+            record_frame(&mut stats, &frame(inputs), &board_after, 0, &config);
+            stats.finesse_faults
+        };
+        assert_eq!(faults(Some(1)), 0);
+        assert_eq!(faults(Some(3)), 1);
+        // no recorded inputs: not counted, even though tapping there is slow
+        assert_eq!(faults(None), 0);
+    }
+
+    #[test]
+    fn test_analyze_replay_tops_out_on_block_out() {
+        // a vertical I on a column-6 pillar reaches the spawn row at x=6:
+        // the I spawn covers it, the O spawn doesn't
+        let mut board = Board::new();
+        board.fill_column(6, 18);
+        let pillar_i = (0..COL_NB as i32)
+            .filter_map(|x| hard_drop(&board, Piece::I, Rotation::East, x))
+            .find(|m| footprint(m).iter().all(|c| c.0 == 6))
+            .unwrap_or_else(|| panic!("vertical I over the pillar"));
+        let config = SearchConfig {
+            beam_width: 20,
+            depth: 1,
+            ..SearchConfig::default()
+        };
+        let topped_out = |next: Piece| {
+            let frames = [ReplayFrame {
+                state: GameState::new(board.clone(), Piece::I, vec![next, Piece::T]),
+                actual_move: pillar_i,
+                lock_frame: 0,
+                inputs: None,
+            }];
+            analyze_replay(&frames, &EvalWeights::default(), &config)
+                .stats
+                .topped_out
+        };
+        assert!(topped_out(Piece::I));
+        assert!(!topped_out(Piece::O));
+    }
+
+    #[test]
+    fn test_pps_timeline_tracks_speed_up() {
+        let template = column_zero_frames(1).remove(0);
+        let at = |lock_frame| ReplayFrame {
+            lock_frame,
+            ..template.clone()
+        };
+        // 2 pps for 10 s at 60 fps, then 4 pps for 5 s
+        let mut frames: Vec<ReplayFrame> = (1..=20).map(|i| at(i * 30)).collect();
+        frames.extend((1..=20).map(|i| at(600 + i * 15)));
+
+        let timeline = pps_timeline(&frames, 120, 60);
+        assert!(!timeline.is_empty());
+        assert!(timeline.iter().all(|&(frame, _)| frame >= 120));
+        for &(frame, pps) in &timeline {
+            if frame <= 600 {
+                assert!((pps - 2.0).abs() < 0.6, "frame {frame}: {pps}");
+            } else if frame >= 720 {
+                assert!((pps - 4.0).abs() < 0.6, "frame {frame}: {pps}");
+            }
+        }
+        let last = timeline.last().map_or(0.0, |s| s.1);
+        assert!(last > timeline[0].1);
+
+        assert!(pps_timeline(&frames, 0, 60).is_empty());
+        assert!(pps_timeline(&[], 120, 60).is_empty());
+    }
+
+    #[test]
+    fn test_moments_to_csv_rows_and_quoting() {
+        let burned = detect_burned_b2b(5, 2, SpinType::NoSpin, 12, 2).unwrap();
+        let misplay = Moment {
+            frame: 3,
+            kind: MomentType::Misplay,
+            analysis: MoveAnalysis {
+                eval_loss: 2.5,
+                severity: Severity::Mistake,
+                ..MoveAnalysis::default()
+            },
+        };
+        let result = AnalysisResult {
+            moments: vec![misplay, burned],
+            ..AnalysisResult::default()
+        };
+        let csv = moments_to_csv(&result);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "frame,type,impact,description,suggestion");
+        assert!(lines[1].starts_with("3,misplay,2.50,\"Mistake: lost 2.50 eval, engine"));
+        assert!(lines[2].starts_with("12,burned_b2b,5.00,\"Plain clear broke"));
+
+        assert_eq!(csv_field("a \"b\", c"), "\"a \"\"b\"\", c\"");
+        assert_eq!(moments_to_csv(&AnalysisResult::default()).lines().count(), 1);
+    }
+
+    #[test]
+    fn test_detect_burned_b2b_threshold() {
+        let burned = detect_burned_b2b(6, 1, SpinType::NoSpin, 12, 6)
+            .unwrap_or_else(|| panic!("b2b 6 on a single is burned at threshold 6"));
+        assert_eq!(burned.frame, 12);
+        assert_eq!(
+            burned.kind,
+            MomentType::BurnedB2B {
+                b2b_before: 6,
+                lost_surge: 6.0
+            }
+        );
+        assert_eq!(burned.impact(), 6.0);
+
+        assert!(detect_burned_b2b(5, 1, SpinType::NoSpin, 12, 6).is_none());
+        // b2b-qualifying clears and non-clears keep the chain
+        assert!(detect_burned_b2b(9, 4, SpinType::NoSpin, 12, 6).is_none());
+        assert!(detect_burned_b2b(9, 1, SpinType::Mini, 12, 6).is_none());
+        assert!(detect_burned_b2b(9, 0, SpinType::NoSpin, 12, 6).is_none());
+        // short chains have no surge to lose
+        let short = detect_burned_b2b(2, 2, SpinType::NoSpin, 0, 2)
+            .unwrap_or_else(|| panic!("at threshold"));
+        assert_eq!(short.impact(), 0.0);
+    }
+
+    #[test]
+    fn test_detect_sz_parity_mistake() {
+        // flat on the left, one-high step from column 6
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = 0b1111 << 6;
+        let board = Board::from(&rows);
+
+        // S one column short of the step: (5, 0) is buried
+        let flat = Move::new(Piece::S, Rotation::North, 4, 0, false);
+        let moment = detect_sz_parity_mistake(&board, Piece::S, &flat)
+            .unwrap_or_else(|| panic!("S next to the step leaves a hole"));
+        let MomentType::ParityHole { better } = moment.kind else {
+            panic!("expected a parity hole, got {:?}", moment.kind);
+        };
+        let mut after = board.clone();
+        after.do_move(&better);
+        assert_eq!(count_holes(&after), 0);
+        assert_eq!(moment.impact(), 1.0);
+
+        // staircase fit: bottom row on the floor, top row on the step
+        let staircase = Move::new(Piece::S, Rotation::North, 5, 0, false);
+        assert!(detect_sz_parity_mistake(&board, Piece::S, &staircase).is_none());
+        // nothing better within a column on pure flat ground
+        let far = Move::new(Piece::S, Rotation::North, 1, 0, false);
+        assert!(detect_sz_parity_mistake(&board, Piece::S, &far).is_none());
+        // only S and Z are checked
+        let t = Move::new(Piece::T, Rotation::North, 4, 0, false);
+        assert!(detect_sz_parity_mistake(&board, Piece::T, &t).is_none());
+    }
+
+    #[test]
+    fn test_board_complexity_empty_vs_cheese() {
+        assert_eq!(board_complexity(&Board::new()), 0.0);
+
+        let mut rows = [0u16; BOARD_HEIGHT];
+        for (y, row) in rows[..10].iter_mut().enumerate() {
+            *row = FULL_ROW & !(1 << (y * 3 % COL_NB));
+        }
+        // jagged top: alternate columns stick up 6 rows
+        for row in rows[10..16].iter_mut() {
+            *row = 0b01_0101_0101;
+        }
+        let cheese = Board::from(&rows);
+        let score = board_complexity(&cheese);
+        assert!(score > 70.0, "cheese scored {score}");
+        assert!(score <= 100.0);
+
+        let mut flat = [0u16; BOARD_HEIGHT];
+        flat[..4].fill(FULL_ROW & !(1 << 9));
+        assert!(board_complexity(&Board::from(&flat)) < score);
+    }
+
+    #[test]
+    fn test_board_interner_dedups_repeats() {
+        let mut interner = BoardInterner::new();
+        let mut board = Board::new();
+        board.spawn_garbage(3, 4);
+
+        let first = interner.intern(&board);
+        let empty = interner.intern(&Board::new());
+        let again = interner.intern(&board.clone());
+        assert_eq!(first, again);
+        assert_ne!(first, empty);
+        assert_eq!(interner.len(), 2);
+        assert!(interner.resolve(again).same_shape(&board));
+        assert!(interner.resolve(empty).is_empty());
+    }
+
+    #[test]
+    fn test_highlights_picks_largest_impact() {
+        let moment = |frame: usize, eval_loss: f32| Moment {
+            frame,
+            kind: MomentType::Misplay,
+            analysis: MoveAnalysis {
+                eval_before: 0.0,
+                eval_after: 0.0,
+                best_eval: 0.0,
+                best_move: Move::none(),
+                best_hold_used: false,
+                coaching_before: CoachingState::default(),
+                coaching_after: CoachingState::default(),
+                best_coaching_state: CoachingState::default(),
+                eval_loss,
+                severity: Severity::Mistake,
+                meter_value: 0.0,
+            },
+        };
+        let result = AnalysisResult {
+            moments: vec![
+                moment(0, 1.0),
+                moment(3, 4.0),
+                moment(5, 2.5),
+                moment(8, 4.0),
+                moment(9, 0.5),
+            ],
+            ..AnalysisResult::default()
+        };
+
+        let top: Vec<usize> = highlights(&result, 2).iter().map(|m| m.frame).collect();
+        assert_eq!(top, vec![3, 8]);
+        assert_eq!(highlights(&result, 3)[2].frame, 5);
+        assert_eq!(highlights(&result, 10).len(), 5);
+        assert!(highlights(&AnalysisResult::default(), 2).is_empty());
+    }
+
+    #[test]
+    fn test_detect_uneven_build_threshold() {
+        // central mountain: 0,2,4,6,8,8,6,4,2,0 -> bumpiness 16
+        let mut mountain = Board::new();
+        for (x, h) in [0, 2, 4, 6, 8, 8, 6, 4, 2, 0].into_iter().enumerate() {
+            mountain.fill_column(x, h);
+        }
+        assert_eq!(surface_bumpiness(&mountain), 16);
+
+        let hit =
+            detect_uneven_build(&mountain, 7, 10).unwrap_or_else(|| panic!("above threshold"));
+        assert_eq!(hit.frame, 7);
+        assert_eq!(hit.kind, MomentType::UnevenSurface { excess: 6 });
+        assert_eq!(hit.impact(), 6.0);
+        assert!(detect_uneven_build(&mountain, 7, 16).is_none());
+
+        // same surface over a holey base is a downstack problem, not this one
+        let mut holey = mountain.clone();
+        for y in 0..3 {
+            holey.fill_row_except(y, 4);
+        }
+        assert!(count_holes(&holey) > UNEVEN_MAX_HOLES);
+        assert!(surface_bumpiness(&holey) > 5);
+        assert!(detect_uneven_build(&holey, 7, 5).is_none());
+    }
 }
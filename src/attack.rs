@@ -1,6 +1,7 @@
 // attack.rs -- TETR.IO Season 2 damage formula
 // piece-agnostic allspin: any piece with spin gets bonus, not just T
 
+use crate::board::Board;
 use crate::header::SpinType;
 
 // base attack table — no spin
@@ -38,13 +39,17 @@ pub enum ComboTable {
     None,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AttackConfig {
     pub pc_garbage: u8,
     pub pc_b2b: u8,
     pub b2b_chaining: bool,
     pub combo_table: ComboTable,
     pub garbage_multiplier: f32,
+    // b2b charging: breaking a 4+ b2b chain releases a surge
+    pub b2b_charging: bool,
+    /// Most garbage lines released onto a player per piece (see `cap_garbage`).
+    pub garbage_cap: Option<u8>,
 }
 
 impl AttackConfig {
@@ -55,6 +60,8 @@ impl AttackConfig {
             b2b_chaining: true,
             combo_table: ComboTable::Multiplier,
             garbage_multiplier: 1.0,
+            b2b_charging: true,
+            garbage_cap: None,
         }
     }
 
@@ -65,10 +72,75 @@ impl AttackConfig {
             b2b_chaining: false,
             combo_table: ComboTable::Multiplier,
             garbage_multiplier: 1.0,
+            b2b_charging: true,
+            garbage_cap: None,
+        }
+    }
+
+    /// Chainable builder, starting from `tetra_league()` values.
+    pub fn builder() -> AttackConfigBuilder {
+        AttackConfigBuilder {
+            config: Self::tetra_league(),
         }
     }
 }
 
+/// Builder for `AttackConfig`; see `AttackConfig::builder()`.
+#[derive(Debug, Clone)]
+pub struct AttackConfigBuilder {
+    config: AttackConfig,
+}
+
+impl AttackConfigBuilder {
+    pub fn pc_garbage(mut self, pc_garbage: u8) -> Self {
+        self.config.pc_garbage = pc_garbage;
+        self
+    }
+
+    pub fn pc_b2b(mut self, pc_b2b: u8) -> Self {
+        self.config.pc_b2b = pc_b2b;
+        self
+    }
+
+    pub fn b2b_chaining(mut self, b2b_chaining: bool) -> Self {
+        self.config.b2b_chaining = b2b_chaining;
+        self
+    }
+
+    pub fn combo_table(mut self, combo_table: ComboTable) -> Self {
+        self.config.combo_table = combo_table;
+        self
+    }
+
+    pub fn garbage_multiplier(mut self, garbage_multiplier: f32) -> Self {
+        self.config.garbage_multiplier = garbage_multiplier;
+        self
+    }
+
+    pub fn charging(mut self, b2b_charging: bool) -> Self {
+        self.config.b2b_charging = b2b_charging;
+        self
+    }
+
+    pub fn garbage_cap(mut self, garbage_cap: Option<u8>) -> Self {
+        self.config.garbage_cap = garbage_cap;
+        self
+    }
+
+    pub fn build(self) -> AttackConfig {
+        self.config
+    }
+}
+
+/// Garbage actually released this turn out of `amount` pending lines; the
+/// rest stays queued. `None` cap releases everything.
+pub fn cap_garbage(amount: u8, config: &AttackConfig) -> u8 {
+    match config.garbage_cap {
+        Some(cap) => amount.min(cap),
+        None => amount,
+    }
+}
+
 fn base_attack(lines: u8, spin: SpinType) -> f32 {
     match spin {
         SpinType::NoSpin => match lines {
@@ -165,6 +237,43 @@ pub fn calculate_attack(
     })
 }
 
+/// Attack for one clear at each garbage multiplier, all else from `config`.
+pub fn attack_curve(
+    lines: u8,
+    spin: SpinType,
+    b2b: u8,
+    combo: u8,
+    config: &AttackConfig,
+    multipliers: &[f32],
+) -> Vec<f32> {
+    let mut swept = config.clone();
+    multipliers
+        .iter()
+        .map(|&m| {
+            swept.garbage_multiplier = m;
+            calculate_attack(lines, spin, b2b, combo, &swept, false)
+        })
+        .collect()
+}
+
+/// Attack for a placement given the board with the piece locked but lines
+/// not yet cleared. Line count comes from full rows in `after_pre_clear`; a
+/// perfect clear is detected when clearing them leaves the board empty.
+/// The board before the piece isn't needed, so snapshots taken around
+/// incoming garbage work as well.
+pub fn attack_for_transition(
+    after_pre_clear: &Board,
+    spin: SpinType,
+    b2b: u8,
+    combo: u8,
+    config: &AttackConfig,
+) -> f32 {
+    let mut cleared = after_pre_clear.clone();
+    let lines = cleared.clear_lines_mask().count_ones() as u8;
+    let is_perfect_clear = lines > 0 && cleared.is_empty();
+    calculate_attack(lines, spin, b2b, combo, config, is_perfect_clear)
+}
+
 pub struct AttackContext<'a> {
     pub lines: u8,
     pub spin: SpinType,
@@ -214,7 +323,7 @@ pub fn calculate_attack_full(ctx: &AttackContext<'_>) -> f32 {
     }
 
     // surge release: non-difficult clear breaks a long B2B chain
-    if let Some(prev_b2b) = b2b_broken_from {
+    if let Some(prev_b2b) = b2b_broken_from.filter(|_| config.b2b_charging) {
         if prev_b2b >= 4 && !is_b2b_eligible {
             attack += 4.0 + (prev_b2b - 4) as f32;
         }
@@ -237,6 +346,7 @@ pub fn calculate_attack_full(ctx: &AttackContext<'_>) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::ClearType;
 
     fn tl() -> AttackConfig {
         AttackConfig::tetra_league()
@@ -400,6 +510,18 @@ mod tests {
         assert_eq!(dmg_0, dmg_5, "ComboTable::None should ignore combo");
     }
 
+    #[test]
+    fn test_cap_garbage() {
+        let capped = AttackConfig::builder().garbage_cap(Some(8)).build();
+        assert_eq!(cap_garbage(5, &capped), 5);
+        assert_eq!(cap_garbage(8, &capped), 8);
+        assert_eq!(cap_garbage(12, &capped), 8);
+
+        let uncapped = AttackConfig::builder().garbage_cap(None).build();
+        assert_eq!(cap_garbage(12, &uncapped), 12);
+        assert_eq!(cap_garbage(u8::MAX, &uncapped), u8::MAX);
+    }
+
     #[test]
     fn test_garbage_multiplier() {
         let mut config = tl();
@@ -451,6 +573,69 @@ mod tests {
         assert_eq!(dmg, 16.0);
     }
 
+    #[test]
+    fn test_penta_no_b2b() {
+        let dmg = calculate_attack(5, SpinType::NoSpin, 0, 0, &tl(), false);
+        assert_eq!(dmg, PENTA as f32);
+    }
+
+    #[test]
+    fn test_penta_b2b() {
+        // flat bonus in QP, chaining bonus in TL
+        let qp_dmg = calculate_attack(5, SpinType::NoSpin, 1, 0, &qp(), false);
+        assert_eq!(qp_dmg, PENTA as f32 + BACK_TO_BACK_BONUS as f32);
+        let tl_dmg = calculate_attack(5, SpinType::NoSpin, 4, 0, &tl(), false);
+        assert_eq!(tl_dmg, PENTA as f32 + b2b_chaining_bonus(4));
+    }
+
+    #[test]
+    fn test_spin_penta() {
+        let dmg = calculate_attack(5, SpinType::Full, 0, 0, &tl(), false);
+        assert_eq!(dmg, SPIN_PENTA as f32);
+        let b2b = calculate_attack(5, SpinType::Full, 1, 0, &qp(), false);
+        assert_eq!(b2b, SPIN_PENTA as f32 + BACK_TO_BACK_BONUS as f32);
+    }
+
+    #[test]
+    fn test_penta_pc_and_combo_modifiers() {
+        let pc = calculate_attack(5, SpinType::NoSpin, 0, 0, &tl(), true);
+        assert_eq!(pc, PENTA as f32 + tl().pc_garbage as f32);
+        let pc_b2b = calculate_attack(5, SpinType::NoSpin, 1, 0, &qp(), true);
+        assert_eq!(
+            pc_b2b,
+            (PENTA + qp().pc_garbage + BACK_TO_BACK_BONUS + qp().pc_b2b) as f32
+        );
+        let combo = calculate_attack(5, SpinType::NoSpin, 0, 1, &tl(), false);
+        assert_eq!(combo, PENTA as f32 * (1.0 + COMBO_BONUS));
+    }
+
+    #[test]
+    fn test_penta_keeps_b2b_and_never_surges() {
+        // 5+ lines is b2b-eligible, so breaking never applies to a penta
+        let with_break = calculate_attack_full(&AttackContext {
+            lines: 5,
+            spin: SpinType::NoSpin,
+            b2b: 0,
+            combo: 0,
+            config: &tl(),
+            is_perfect_clear: false,
+            b2b_broken_from: Some(8),
+            clears_garbage: false,
+        });
+        assert_eq!(with_break, PENTA as f32);
+        assert_eq!(ClearType::from_lines(5), ClearType::Penta);
+    }
+
+    #[test]
+    fn test_attack_curve_scales_with_multiplier() {
+        let curve = attack_curve(2, SpinType::Full, 0, 0, &tl(), &[0.5, 1.0, 2.0]);
+        assert_eq!(curve.len(), 3);
+        assert_eq!(curve[1], calculate_attack(2, SpinType::Full, 0, 0, &tl(), false));
+        assert!((curve[2] - 2.0 * curve[1]).abs() < 1e-4);
+        assert!((curve[0] - 0.5 * curve[1]).abs() < 1e-4);
+        assert!(attack_curve(4, SpinType::NoSpin, 0, 0, &tl(), &[]).is_empty());
+    }
+
     #[test]
     fn test_combo_multiplier_max_semantics() {
         // combo=2, base=4 (quad, no b2b)
@@ -655,4 +840,87 @@ mod tests {
         });
         assert_eq!(without, with);
     }
+
+    #[test]
+    fn test_builder_reproduces_tetra_league() {
+        let built = AttackConfig::builder()
+            .pc_garbage(5)
+            .pc_b2b(2)
+            .b2b_chaining(true)
+            .combo_table(ComboTable::Multiplier)
+            .garbage_multiplier(1.0)
+            .charging(true)
+            .build();
+        assert_eq!(built, AttackConfig::tetra_league());
+    }
+
+    #[test]
+    fn test_builder_quick_play_and_charging_off() {
+        let qp_built = AttackConfig::builder()
+            .pc_garbage(3)
+            .b2b_chaining(false)
+            .build();
+        assert_eq!(qp_built, qp());
+
+        let no_charge = AttackConfig::builder().charging(false).build();
+        let dmg = calculate_attack_full(&AttackContext {
+            lines: 1,
+            spin: SpinType::NoSpin,
+            b2b: 0,
+            combo: 0,
+            config: &no_charge,
+            is_perfect_clear: false,
+            b2b_broken_from: Some(4),
+            clears_garbage: false,
+        });
+        assert_eq!(dmg, 0.0, "surge release requires b2b charging");
+    }
+
+    #[test]
+    fn test_attack_for_transition_tetris() {
+        use crate::board::{BOARD_HEIGHT, FULL_ROW};
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[..4].fill(FULL_ROW & !(1 << 9));
+        rows[..4].fill(FULL_ROW);
+        rows[4] = 0b1;
+        let after = Board::from(&rows);
+
+        let dmg = attack_for_transition(&after, SpinType::NoSpin, 0, 0, &tl());
+        assert_eq!(dmg, calculate_attack(4, SpinType::NoSpin, 0, 0, &tl(), false));
+        assert_eq!(dmg, 4.0);
+    }
+
+    #[test]
+    fn test_attack_for_transition_perfect_clear() {
+        use crate::board::{BOARD_HEIGHT, FULL_ROW};
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[..4].fill(FULL_ROW);
+        let after = Board::from(&rows);
+
+        let dmg = attack_for_transition(&after, SpinType::NoSpin, 0, 0, &tl());
+        assert_eq!(dmg, calculate_attack(4, SpinType::NoSpin, 0, 0, &tl(), true));
+        assert_eq!(dmg, 4.0 + 5.0);
+
+        // no line clear → no attack
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = 0b1111;
+        let no_clear = Board::from(&rows);
+        assert_eq!(
+            attack_for_transition(&no_clear, SpinType::NoSpin, 0, 0, &tl()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_attack_for_transition_garbage_under_the_clear() {
+        let mut after = Board::new();
+        after.fill_row_except(0, 9);
+        // two garbage lines push the stack up, then a vertical I fills the
+        // well at rows 2..6: only the lifted row clears
+        after.spawn_garbage(2, 3);
+        after.fill_column(9, 6);
+
+        let dmg = attack_for_transition(&after, SpinType::NoSpin, 0, 0, &tl());
+        assert_eq!(dmg, calculate_attack(1, SpinType::NoSpin, 0, 0, &tl(), false));
+    }
 }
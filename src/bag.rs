@@ -1,4 +1,5 @@
 use crate::header::{Piece, ALL_PIECES, PIECE_NB};
+use crate::transposition::splitmix64;
 
 #[derive(Debug, Clone)]
 pub(crate) struct BagTracker {
@@ -85,6 +86,34 @@ pub(crate) fn extend_queue(queue: &[Piece], current: Piece, hold: Option<Piece>)
     extended
 }
 
+/// Seeded 7-bag randomizer: each run of 7 pieces is a shuffle of all seven.
+#[derive(Debug, Clone)]
+pub struct SevenBag {
+    rng_state: u64,
+    bag: Vec<Piece>,
+}
+
+impl SevenBag {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng_state: seed,
+            bag: Vec::with_capacity(PIECE_NB),
+        }
+    }
+
+    pub fn next_piece(&mut self) -> Piece {
+        if self.bag.is_empty() {
+            self.bag.extend_from_slice(&ALL_PIECES);
+            // Fisher-Yates; pieces are popped from the back
+            for i in (1..PIECE_NB).rev() {
+                let j = (splitmix64(&mut self.rng_state) % (i as u64 + 1)) as usize;
+                self.bag.swap(i, j);
+            }
+        }
+        self.bag.pop().unwrap_or(Piece::I)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,4 +282,24 @@ mod tests {
         assert_eq!(tracker.count(), 0);
         assert_eq!(tracker.remaining().len(), 7);
     }
+
+    #[test]
+    fn test_seven_bag_each_bag_is_a_permutation() {
+        let mut bag = SevenBag::new(123);
+        for _ in 0..4 {
+            let mut drawn: Vec<Piece> = (0..7).map(|_| bag.next_piece()).collect();
+            drawn.sort_by_key(|&p| p as u8);
+            assert_eq!(drawn, ALL_PIECES.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_seven_bag_deterministic_per_seed() {
+        let draw = |seed| {
+            let mut bag = SevenBag::new(seed);
+            (0..21).map(|_| bag.next_piece()).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(9), draw(9));
+        assert_ne!(draw(9), draw(10));
+    }
 }
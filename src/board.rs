@@ -2,10 +2,12 @@
 // Y-up convention (row 0 = bottom), matching Cobra
 
 use crate::header::*;
+use crate::transposition::splitmix64;
 use std::fmt;
 
 pub const BOARD_HEIGHT: usize = 40;
 pub const FULL_ROW: u16 = (1 << COL_NB) - 1; // 0x3FF
+pub const VISIBLE_HEIGHT: usize = 20; // rows >= 20 are the hidden buffer
 
 pub struct Board {
     pub rows: [u16; BOARD_HEIGHT],
@@ -20,6 +22,70 @@ impl Board {
         }
     }
 
+    /// Flat row-major cells, bottom row first (TETR.IO field dump). Any
+    /// nonzero cell is filled. Accepts 10x40 or the visible 10x20.
+    pub fn from_cells(cells: &[u8]) -> Result<Board, String> {
+        if cells.len() != COL_NB * BOARD_HEIGHT && cells.len() != COL_NB * VISIBLE_HEIGHT {
+            return Err(format!(
+                "expected {} or {} cells, got {}",
+                COL_NB * BOARD_HEIGHT,
+                COL_NB * VISIBLE_HEIGHT,
+                cells.len()
+            ));
+        }
+        let mut rows = [0u16; BOARD_HEIGHT];
+        for (row, chunk) in rows.iter_mut().zip(cells.chunks_exact(COL_NB)) {
+            for (x, &cell) in chunk.iter().enumerate() {
+                if cell != 0 {
+                    *row |= 1 << x;
+                }
+            }
+        }
+        Ok(Board::from(&rows))
+    }
+
+    /// Inverse of `from_cells`: 10x40 cells of 0/1, bottom row first.
+    pub fn to_cells(&self) -> Vec<u8> {
+        self.rows
+            .iter()
+            .flat_map(|&row| (0..COL_NB).map(move |x| ((row >> x) & 1) as u8))
+            .collect()
+    }
+
+    /// Copy-pasteable test fixture: 40 strings of `'0'`/`'1'`, top row first,
+    /// leftmost column first in each string.
+    pub fn to_fixture_rows(&self) -> [String; BOARD_HEIGHT] {
+        std::array::from_fn(|i| {
+            let row = self.rows[BOARD_HEIGHT - 1 - i];
+            (0..COL_NB)
+                .map(|x| if (row >> x) & 1 != 0 { '1' } else { '0' })
+                .collect()
+        })
+    }
+
+    /// Inverse of `to_fixture_rows`. Fewer than 40 rows fill the bottom of
+    /// the board, so the last string is always row 0.
+    pub fn from_fixture_rows<S: AsRef<str>>(rows: &[S]) -> Result<Board, String> {
+        if rows.len() > BOARD_HEIGHT {
+            return Err(format!("expected at most {} rows, got {}", BOARD_HEIGHT, rows.len()));
+        }
+        let mut out = [0u16; BOARD_HEIGHT];
+        for (y, line) in rows.iter().rev().enumerate() {
+            let line = line.as_ref();
+            if line.len() != COL_NB {
+                return Err(format!("row {y}: expected {COL_NB} cells, got {:?}", line));
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '1' => out[y] |= 1 << x,
+                    '0' => {}
+                    _ => return Err(format!("row {y}: unexpected cell {c:?}")),
+                }
+            }
+        }
+        Ok(Board::from(&out))
+    }
+
     pub fn occupied(&self, x: i32, y: i32) -> bool {
         let yu = y as usize;
         if yu >= BOARD_HEIGHT {
@@ -100,8 +166,9 @@ impl Board {
         }
     }
 
+    /// C++ name for `is_empty`.
     pub fn empty(&self) -> bool {
-        self.rows.iter().all(|&r| r == 0)
+        self.is_empty()
     }
 
     pub fn line_clears(&self) -> Bitboard {
@@ -114,6 +181,19 @@ impl Board {
         result
     }
 
+    /// Same result as `line_clears`, from the column cache: AND of all
+    /// columns, so one pass over 10 words instead of 40 row compares.
+    #[inline]
+    pub fn filled_row_mask(&self) -> u64 {
+        self.cols.iter().fold(u64::MAX, |acc, &c| acc & c)
+    }
+
+    /// Number of occupied cells.
+    #[inline]
+    pub fn total_cells(&self) -> u32 {
+        self.cols.iter().map(|c| c.count_ones()).sum()
+    }
+
     pub fn clear(&mut self) {
         self.rows = [0; BOARD_HEIGHT];
         self.cols = [0; COL_NB];
@@ -135,6 +215,16 @@ impl Board {
         self.rebuild_cols();
     }
 
+    /// Clear every full row. Returns the mask of cleared rows (pre-clear
+    /// row indices), 0 if nothing cleared.
+    pub fn clear_lines_mask(&mut self) -> Bitboard {
+        let clears = self.line_clears();
+        if clears != 0 {
+            self.clear_lines(clears);
+        }
+        clears
+    }
+
     pub fn place(&mut self, m: &Move) {
         let pc = m.cells();
         let x = m.x();
@@ -157,6 +247,29 @@ impl Board {
         }
     }
 
+    /// Fill column `x` from the floor up to (not including) `up_to_row`.
+    pub fn fill_column(&mut self, x: usize, up_to_row: usize) {
+        debug_assert!(x < COL_NB);
+        let top = up_to_row.min(BOARD_HEIGHT);
+        for row in &mut self.rows[..top] {
+            *row |= 1 << x;
+        }
+        self.cols[x] |= (1u64 << top) - 1;
+    }
+
+    /// Overwrite row `y` with a garbage row: full except `hole_x`.
+    pub fn fill_row_except(&mut self, y: usize, hole_x: usize) {
+        debug_assert!(y < BOARD_HEIGHT && hole_x < COL_NB);
+        self.rows[y] = FULL_ROW & !(1u16 << hole_x);
+        for (x, col) in self.cols.iter_mut().enumerate() {
+            if x == hole_x {
+                *col &= !(1u64 << y);
+            } else {
+                *col |= 1u64 << y;
+            }
+        }
+    }
+
     pub fn spawn_garbage(&mut self, lines: i32, x: i32) {
         debug_assert!(is_ok_x(x));
         debug_assert!(lines > 0);
@@ -171,13 +284,69 @@ impl Board {
         self.rebuild_cols();
     }
 
+    /// Like spawn_garbage, but each line after the first moves its hole with
+    /// probability `messiness` (TETR.IO-style). The top garbage row keeps
+    /// `base_hole`; `rng_state` advances so repeated calls stay deterministic.
+    pub fn insert_garbage_messy(
+        &mut self,
+        lines: u8,
+        base_hole: usize,
+        messiness: f32,
+        rng_state: &mut u64,
+    ) {
+        debug_assert!(base_hole < COL_NB);
+        let n = (lines as usize).min(BOARD_HEIGHT);
+        if n == 0 {
+            return;
+        }
+        for y in (n..BOARD_HEIGHT).rev() {
+            self.rows[y] = self.rows[y - n];
+        }
+        let mut hole = base_hole;
+        for i in 0..n {
+            if i > 0 {
+                let roll = (splitmix64(rng_state) >> 40) as f32 / (1u32 << 24) as f32;
+                if roll < messiness {
+                    // pick uniformly among the other 9 columns
+                    let shift = 1 + (splitmix64(rng_state) % (COL_NB as u64 - 1)) as usize;
+                    hole = (hole + shift) % COL_NB;
+                }
+            }
+            self.rows[n - 1 - i] = FULL_ROW & !(1u16 << hole);
+        }
+        self.rebuild_cols();
+    }
+
+    /// Binary PPM (P6) of the visible field, `cell_size` pixels per cell,
+    /// row 0 at the bottom of the image.
+    #[cfg(feature = "render")]
+    pub fn to_ppm(&self, cell_size: u32) -> Vec<u8> {
+        const FILLED: [u8; 3] = [0xC8, 0xC8, 0xC8];
+        const EMPTY: [u8; 3] = [0x10, 0x10, 0x10];
+
+        let cell = cell_size as usize;
+        let width = COL_NB * cell;
+        let height = VISIBLE_HEIGHT * cell;
+        let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+        out.reserve(width * height * 3);
+        for py in 0..height {
+            let y = VISIBLE_HEIGHT - 1 - py / cell;
+            for px in 0..width {
+                let x = px / cell;
+                let rgb = if self.rows[y] & (1 << x) != 0 { FILLED } else { EMPTY };
+                out.extend_from_slice(&rgb);
+            }
+        }
+        out
+    }
+
     pub fn do_move(&mut self, m: &Move) -> i32 {
         if !self.legal_lock_placement(m) {
             return 0;
         }
 
         self.place(m);
-        let clears = self.line_clears();
+        let clears = self.filled_row_mask();
         if clears == 0 {
             return 0;
         }
@@ -186,11 +355,13 @@ impl Board {
         popcount(clears) as i32
     }
 
-    /// Max occupied row index + 1 (= height)
+    /// No cells anywhere: the perfect-clear check. Reads `rows`, so it holds
+    /// even after a direct row edit that hasn't been through `rebuild_cols`.
     pub fn is_empty(&self) -> bool {
         self.rows.iter().all(|&r| r == 0)
     }
 
+    /// Max occupied row index + 1 (= height)
     pub fn height(&self) -> u32 {
         for y in (0..BOARD_HEIGHT).rev() {
             if self.rows[y] != 0 {
@@ -200,6 +371,17 @@ impl Board {
         0
     }
 
+    /// Top occupied row + 1 in column `x`, from the column cache.
+    #[inline]
+    pub fn column_height(&self, x: usize) -> usize {
+        (u64::BITS - self.cols[x].leading_zeros()) as usize
+    }
+
+    /// Column heights (top occupied row + 1), left to right.
+    pub fn surface_profile(&self) -> [u8; COL_NB] {
+        std::array::from_fn(|x| self.column_height(x) as u8)
+    }
+
     pub fn to_string_with_move(&self, m: &Move) -> String {
         let mut output = self.to_string();
         if !self.obstructed_move(m) {
@@ -227,6 +409,68 @@ impl Board {
     pub fn row(&self, y: usize) -> u16 {
         self.rows[y]
     }
+
+    /// Build-independent content hash for deduplicating boards across tools.
+    /// FNV-1a (64-bit) over `row(y)` for y = BOARD_HEIGHT-1 down to 0, each
+    /// row fed as 2 little-endian bytes. Unlike the Zobrist hash this does not
+    /// depend on any key table, so equal boards hash equally everywhere.
+    pub fn content_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+        let mut hash = FNV_OFFSET;
+        for y in (0..BOARD_HEIGHT).rev() {
+            for byte in self.row(y).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    /// Overwrite column `x` with bitboard `col` (bit y = row y), keeping
+    /// rows and the cols cache in sync. Bits above BOARD_HEIGHT are dropped.
+    pub fn set_column(&mut self, x: usize, col: Bitboard) {
+        debug_assert!(x < COL_NB);
+        let col = col & ((1u64 << BOARD_HEIGHT) - 1);
+        let mask = 1u16 << x;
+        for y in 0..BOARD_HEIGHT {
+            if col & (1u64 << y) != 0 {
+                self.rows[y] |= mask;
+            } else {
+                self.rows[y] &= !mask;
+            }
+        }
+        self.cols[x] = col;
+    }
+
+    /// Left-right mirror image: column `x` becomes column `COL_NB - 1 - x`.
+    pub fn mirror_x(&self) -> Board {
+        let mut mirrored = Board::new();
+        for (y, &row) in self.rows.iter().enumerate() {
+            mirrored.rows[y] = (row.reverse_bits() >> (u16::BITS as usize - COL_NB)) & FULL_ROW;
+        }
+        for (x, &col) in self.cols.iter().enumerate() {
+            mirrored.cols[COL_NB - 1 - x] = col;
+        }
+        mirrored
+    }
+
+    /// Same filled cells as `other`.
+    pub fn same_shape(&self, other: &Board) -> bool {
+        self.rows == other.rows
+    }
+
+    /// OR `other`'s cells into this board (fixture construction, e.g. adding
+    /// a garbage pattern under a shape). Overlapping cells are idempotent:
+    /// a cell filled on both boards stays filled. No line clears are applied.
+    pub fn overlay(&mut self, other: &Board) {
+        for x in 0..COL_NB {
+            let col = self.cols[x] | other.cols[x];
+            if col != self.cols[x] {
+                self.set_column(x, col);
+            }
+        }
+    }
 }
 
 impl Clone for Board {
@@ -238,12 +482,74 @@ impl Clone for Board {
     }
 }
 
+// row bitmask snapshot <-> Board; cols cache is rebuilt on the way in
+impl From<&[u16; BOARD_HEIGHT]> for Board {
+    fn from(rows: &[u16; BOARD_HEIGHT]) -> Self {
+        let mut board = Board::new();
+        for (dst, &row) in board.rows.iter_mut().zip(rows.iter()) {
+            *dst = row & FULL_ROW;
+        }
+        board.rebuild_cols();
+        board
+    }
+}
+
+impl From<&Board> for [u16; BOARD_HEIGHT] {
+    fn from(board: &Board) -> Self {
+        board.rows
+    }
+}
+
 impl Default for Board {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Reads a text position: board rows top-down (`#`, `X` or `1` filled;
+/// `.`, `_` or `0` empty; 10 cells each, missing rows are empty), then a
+/// final line `piece=T queue=IOLJ` (queue optional). Blank lines are ignored.
+pub fn parse_position(s: &str) -> Result<(Board, Piece, Vec<Piece>), String> {
+    let mut lines: Vec<&str> = s.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let spec = lines.pop().ok_or("empty position")?;
+
+    let mut piece = None;
+    let mut queue = Vec::new();
+    for field in spec.split_whitespace() {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got {field:?}"))?;
+        let parse = |c: char| Piece::from_char(c).ok_or_else(|| format!("unknown piece {c:?}"));
+        match key {
+            "piece" => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => piece = Some(parse(c)?),
+                    _ => return Err(format!("piece must be one letter, got {value:?}")),
+                }
+            }
+            "queue" => queue = value.chars().map(parse).collect::<Result<_, _>>()?,
+            _ => return Err(format!("unknown field {key:?}")),
+        }
+    }
+    let piece = piece.ok_or("missing piece=")?;
+
+    let rows: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' | 'X' | '1' => '1',
+                    '.' | '_' | '0' => '0',
+                    other => other,
+                })
+                .collect()
+        })
+        .collect();
+    let board = Board::from_fixture_rows(&rows)?;
+    Ok((board, piece, queue))
+}
+
 // -- MoveInfo --
 
 pub struct MoveInfo {
@@ -314,7 +620,7 @@ impl State {
             clear: clear_count,
             b2b: self.b2b,
             combo: self.combo,
-            pc: self.board.empty(),
+            pc: self.board.is_empty(),
         }
     }
 }
@@ -352,7 +658,24 @@ mod tests {
     #[test]
     fn test_empty_board() {
         let board = Board::new();
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn test_is_empty_after_perfect_clear() {
+        let mut board = Board::new();
+        board.rows[0] = FULL_ROW & !(0xF << 3);
+        board.rebuild_cols();
+        assert!(!board.is_empty());
+        assert_eq!(board.do_move(&Move::new(Piece::I, Rotation::North, 4, 0, false)), 1);
+        assert!(board.is_empty());
         assert!(board.empty());
+
+        // a direct row edit counts before the column cache is rebuilt
+        let mut one_cell = Board::new();
+        one_cell.rows[5] = 1 << 3;
+        assert!(!one_cell.is_empty());
+        assert!(!one_cell.empty());
     }
 
     #[test]
@@ -435,4 +758,309 @@ mod tests {
         assert_eq!(clears, 0);
         assert_eq!(board.rows, before);
     }
+
+    #[test]
+    fn test_set_column_syncs_rows() {
+        let mut board = Board::new();
+        board.rows[2] = 1 << 7;
+        board.rebuild_cols();
+
+        board.set_column(7, 0b1001);
+        assert!(board.occupied(7, 0));
+        assert!(board.occupied(7, 3));
+        assert!(!board.occupied(7, 2));
+        assert_eq!(board.col(7), 0b1001);
+    }
+
+    #[test]
+    fn test_mirror_x() {
+        let mut board = Board::new();
+        board.place(&Move::new(Piece::L, Rotation::North, 1, 0, false));
+        let mirrored = board.mirror_x();
+        assert_eq!(mirrored.rows[0], 0b111 << 7);
+        assert_eq!(mirrored.rows[1], 1 << 7);
+        assert_eq!(mirrored.col(7), 0b11);
+        assert!(!mirrored.same_shape(&board));
+        assert!(mirrored.mirror_x().same_shape(&board));
+        assert!(Board::new().same_shape(&Board::new().mirror_x()));
+    }
+
+    #[test]
+    fn test_overlay_union_and_hash() {
+        use crate::transposition::get_zobrist_keys;
+
+        let mut shape = Board::new();
+        shape.place(&Move::new(Piece::T, Rotation::North, 4, 2, false));
+        let mut garbage = Board::new();
+        garbage.spawn_garbage(2, 4);
+
+        let mut merged = shape.clone();
+        merged.overlay(&garbage);
+
+        for y in 0..BOARD_HEIGHT {
+            assert_eq!(merged.rows[y], shape.rows[y] | garbage.rows[y]);
+        }
+        let cached = merged.cols;
+        merged.rebuild_cols();
+        assert_eq!(merged.cols, cached);
+
+        // same cells built directly hash identically
+        let mut direct = Board::new();
+        direct.spawn_garbage(2, 4);
+        direct.place(&Move::new(Piece::T, Rotation::North, 4, 2, false));
+        let keys = get_zobrist_keys();
+        assert_eq!(keys.hash_board(&merged), keys.hash_board(&direct));
+
+        // overlaying again changes nothing
+        let before = merged.rows;
+        merged.overlay(&garbage);
+        assert_eq!(merged.rows, before);
+    }
+
+    #[test]
+    fn test_content_hash_matches_for_identical_boards() {
+        let mut a = Board::new();
+        a.place(&Move::new(Piece::L, Rotation::North, 2, 0, false));
+        a.spawn_garbage(1, 7);
+
+        let mut b = Board::new();
+        b.rows[0] = FULL_ROW & !(1 << 7);
+        b.rows[1] = a.rows[1];
+        b.rows[2] = a.rows[2];
+        b.rebuild_cols();
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        b.rows[5] |= 1;
+        assert_ne!(a.content_hash(), b.content_hash());
+    }
+
+    #[test]
+    fn test_content_hash_empty_board_is_fixed() {
+        // fixed value: FNV-1a over 80 zero bytes
+        let mut expected: u64 = 0xCBF2_9CE4_8422_2325;
+        for _ in 0..(BOARD_HEIGHT * 2) {
+            expected = expected.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+        assert_eq!(Board::new().content_hash(), expected);
+    }
+
+    #[test]
+    fn test_row_snapshot_round_trip() {
+        use crate::transposition::get_zobrist_keys;
+
+        let mut board = Board::new();
+        board.spawn_garbage(3, 2);
+        board.place(&Move::new(Piece::S, Rotation::North, 5, 4, false));
+
+        let rows: [u16; BOARD_HEIGHT] = (&board).into();
+        let back = Board::from(&rows);
+        assert_eq!(back.rows, board.rows);
+        assert_eq!(back.cols, board.cols);
+        let keys = get_zobrist_keys();
+        assert_eq!(keys.hash_board(&back), keys.hash_board(&board));
+
+        // bits past the 10th column are dropped
+        let mut noisy = rows;
+        noisy[0] |= 1 << 12;
+        assert_eq!(Board::from(&noisy).rows, board.rows);
+    }
+
+    fn garbage_holes(board: &Board, lines: usize) -> Vec<u32> {
+        (0..lines)
+            .map(|y| (!board.rows[y] & FULL_ROW).trailing_zeros())
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_garbage_messy_clean_keeps_one_column() {
+        let mut board = Board::new();
+        let mut rng = 42;
+        board.insert_garbage_messy(8, 3, 0.0, &mut rng);
+        assert_eq!(garbage_holes(&board, 8), vec![3; 8]);
+        assert_eq!(board.rows[8], 0);
+        let cached = board.cols;
+        board.rebuild_cols();
+        assert_eq!(board.cols, cached);
+    }
+
+    #[test]
+    fn test_insert_garbage_messy_full_messiness_changes_holes() {
+        let mut a = Board::new();
+        let mut rng_a = 7;
+        a.insert_garbage_messy(10, 5, 1.0, &mut rng_a);
+        let holes = garbage_holes(&a, 10);
+        assert_eq!(holes[9], 5, "top garbage row keeps base_hole");
+        for w in holes.windows(2) {
+            assert_ne!(w[0], w[1]);
+        }
+        for &row in &a.rows[..10] {
+            assert_eq!(row.count_ones(), 9);
+        }
+
+        // same seed, same garbage
+        let mut b = Board::new();
+        let mut rng_b = 7;
+        b.insert_garbage_messy(10, 5, 1.0, &mut rng_b);
+        assert_eq!(a.rows, b.rows);
+        assert_eq!(rng_a, rng_b);
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_to_ppm_dimensions_and_pixels() {
+        let mut board = Board::new();
+        board.spawn_garbage(1, 9);
+        let ppm = board.to_ppm(4);
+
+        let header = b"P6\n40 80\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 40 * 80 * 3);
+
+        // bottom-left pixel is garbage, bottom-right is the hole
+        let pixel = |px: usize, py: usize| {
+            let at = header.len() + (py * 40 + px) * 3;
+            &ppm[at..at + 3]
+        };
+        assert_ne!(pixel(0, 79), pixel(39, 79));
+        assert_eq!(pixel(39, 79), pixel(0, 0));
+    }
+
+    #[test]
+    fn test_cells_round_trip() {
+        let mut board = Board::new();
+        board.spawn_garbage(2, 6);
+        board.place(&Move::new(Piece::J, Rotation::East, 1, 3, false));
+
+        let cells = board.to_cells();
+        assert_eq!(cells.len(), COL_NB * BOARD_HEIGHT);
+        assert_eq!(cells[6], 0);
+        assert_eq!(cells[7], 1);
+
+        let back = Board::from_cells(&cells).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(back.rows, board.rows);
+        assert_eq!(back.cols, board.cols);
+
+        // colour ids count as filled; the visible-only 10x20 form is accepted
+        let colored: Vec<u8> = cells.iter().map(|&c| c * 5).collect();
+        let visible = Board::from_cells(&colored[..COL_NB * VISIBLE_HEIGHT])
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(visible.rows, board.rows);
+    }
+
+    #[test]
+    fn test_from_cells_rejects_wrong_length() {
+        let err = Board::from_cells(&[0; 199]).err().unwrap_or_default();
+        assert!(err.contains("199"), "{err}");
+        assert!(Board::from_cells(&[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let text = "
+            ..........
+            #.........
+            ####.#####
+            piece=T queue=IOLJ
+        ";
+        let (board, piece, queue) =
+            parse_position(text).unwrap_or_else(|e| panic!("well-formed: {e}"));
+        assert_eq!(board.rows[0], FULL_ROW & !(1 << 4));
+        assert_eq!(board.rows[1], 1);
+        assert_eq!(board.height(), 2);
+        assert_eq!(piece, Piece::T);
+        assert_eq!(queue, vec![Piece::I, Piece::O, Piece::L, Piece::J]);
+
+        let (_, _, no_queue) = parse_position("piece=s").unwrap_or_else(|e| panic!("{e}"));
+        assert!(no_queue.is_empty());
+
+        assert!(parse_position("..........\npiece=Q").is_err());
+        assert!(parse_position("piece=T queue=IOXJ").is_err());
+        assert!(parse_position("piece=TI").is_err());
+        assert!(parse_position("#########\npiece=T").is_err());
+        assert!(parse_position("queue=IO").is_err());
+        assert!(parse_position("").is_err());
+    }
+
+    #[test]
+    fn test_fixture_rows_round_trip() {
+        let mut board = Board::new();
+        board.place(&Move::new(Piece::L, Rotation::North, 6, 0, false));
+        board.place(&Move::new(Piece::I, Rotation::East, 0, 2, false));
+        board.rows[BOARD_HEIGHT - 1] = 0b1;
+        board.cols[0] |= 1 << (BOARD_HEIGHT - 1);
+
+        let rows = board.to_fixture_rows();
+        assert_eq!(rows[0], "1000000000");
+        assert_eq!(rows[BOARD_HEIGHT - 1], "1000011100");
+
+        let back = Board::from_fixture_rows(&rows).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(back.rows, board.rows);
+        assert_eq!(back.cols, board.cols);
+
+        let short = Board::from_fixture_rows(&["0000001000", "1000011100"])
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(short.rows[0], board.rows[0]);
+        assert_eq!(short.rows[1], 1 << 6);
+        assert!(Board::from_fixture_rows(&["01"]).is_err());
+        assert!(Board::from_fixture_rows(&["000000000x"]).is_err());
+    }
+
+    #[test]
+    fn test_filled_row_mask_and_total_cells_match_row_scan() {
+        let mut stacked = Board::new();
+        stacked.spawn_garbage(3, 5);
+        stacked.place(&Move::new(Piece::I, Rotation::North, 5, 3, false));
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = FULL_ROW;
+        rows[2] = FULL_ROW;
+        rows[3] = 0b1011;
+        rows[BOARD_HEIGHT - 1] = FULL_ROW;
+        let boards = [Board::new(), stacked, Board::from(&rows), Board::from(&[FULL_ROW; BOARD_HEIGHT])];
+
+        for b in &boards {
+            let naive_mask = (0..BOARD_HEIGHT)
+                .filter(|&y| b.rows[y] == FULL_ROW)
+                .fold(0u64, |m, y| m | 1 << y);
+            let naive_cells: u32 = b.rows.iter().map(|r| r.count_ones()).sum();
+            assert_eq!(b.filled_row_mask(), naive_mask);
+            assert_eq!(b.filled_row_mask(), b.line_clears());
+            assert_eq!(b.total_cells(), naive_cells);
+        }
+        assert_eq!(boards[2].filled_row_mask(), 0b101 | 1 << (BOARD_HEIGHT - 1));
+        assert_eq!(boards[3].total_cells(), (COL_NB * BOARD_HEIGHT) as u32);
+    }
+
+    #[test]
+    fn test_column_height() {
+        assert_eq!(Board::new().column_height(3), 0);
+
+        // covered gap below the top cell doesn't matter
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = 1 << 3;
+        rows[5] = 1 << 3;
+        let board = Board::from(&rows);
+        assert_eq!(board.column_height(3), 6);
+        assert_eq!(board.column_height(2), 0);
+        assert_eq!(board.surface_profile()[3], 6);
+    }
+
+    #[test]
+    fn test_fill_column_and_row_except() {
+        let mut board = Board::new();
+        board.fill_column(7, 5);
+        assert_eq!(board.column_height(7), 5);
+        assert!((0..5).all(|y| board.occupied(7, y)));
+        assert!(!board.occupied(7, 5));
+        assert_eq!(board.total_cells(), 5);
+
+        board.fill_row_except(2, 7);
+        assert_eq!(board.rows[2].count_ones(), COL_NB as u32 - 1);
+        assert!(!board.occupied(7, 2));
+        assert_eq!(board.total_cells(), 4 + 9);
+
+        // cached columns stay in sync with rows, so hashing agrees
+        let rebuilt = Board::from(&board.rows);
+        assert_eq!(rebuilt.cols, board.cols);
+        assert_eq!(rebuilt.content_hash(), board.content_hash());
+    }
 }
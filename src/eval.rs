@@ -1,13 +1,19 @@
 // eval.rs -- board-quality-only evaluation
 // presim (beam search) handles tactics; eval scores board shape only
 
-use crate::board::Board;
+use crate::board::{Board, BOARD_HEIGHT, FULL_ROW, VISIBLE_HEIGHT};
 use crate::header::*;
 
+/// Depth bands for `EvalWeights::hole_weights`; deeper holes share the last.
+pub const HOLE_DEPTH_BANDS: usize = 4;
+
 #[derive(Clone, Debug)]
 pub struct EvalWeights {
     // -- existing board-shape features --
     pub holes: f32,
+    /// Per-hole weight by depth band (see `hole_depths`). All zero means the
+    /// scalar `holes` weight applies to every hole instead.
+    pub hole_weights: [f32; HOLE_DEPTH_BANDS],
     pub cell_coveredness: f32,
     pub height: f32,
     pub height_upper_half: f32,
@@ -19,12 +25,18 @@ pub struct EvalWeights {
     // -- structural pattern bonuses --
     pub tsd_overhang: f32,
     pub four_wide_well: f32,
+    pub tetris_ready: f32,
+    pub spin_potential: f32,
+    pub garbage_alignment: f32,
+    pub pillar: f32,
+    pub stack_volume: f32,
 }
 
 impl Default for EvalWeights {
     fn default() -> Self {
         Self {
             holes: -4.0,
+            hole_weights: [0.0; HOLE_DEPTH_BANDS],
             cell_coveredness: -0.5,
             height: -0.2,
             height_upper_half: -1.0,
@@ -35,6 +47,57 @@ impl Default for EvalWeights {
             well_depth: 0.2,
             tsd_overhang: 6.0,
             four_wide_well: 1.5,
+            tetris_ready: 0.0,
+            spin_potential: 0.0,
+            garbage_alignment: 0.0,
+            pillar: 0.0,
+            stack_volume: 0.0,
+        }
+    }
+}
+
+impl EvalWeights {
+    /// Deliberately weak weights: blind to holes, covered cells and surface
+    /// quality. Only the height panic terms stay, so it
+    /// still survives a while.
+    fn weak() -> Self {
+        Self {
+            holes: 0.0,
+            cell_coveredness: 0.0,
+            bumpiness: 0.0,
+            bumpiness_sq: 0.0,
+            row_transitions: 0.0,
+            well_depth: 0.0,
+            tsd_overhang: 0.0,
+            four_wide_well: 0.0,
+            ..Self::default()
+        }
+    }
+
+    /// Strength knob for bot opponents: linear blend from `weak()` at 0 to
+    /// the default weights at 1. `difficulty` is clamped to [0, 1].
+    pub fn scaled(difficulty: f32) -> EvalWeights {
+        let t = difficulty.clamp(0.0, 1.0);
+        let (w, s) = (Self::weak(), Self::default());
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        EvalWeights {
+            holes: lerp(w.holes, s.holes),
+            hole_weights: std::array::from_fn(|i| lerp(w.hole_weights[i], s.hole_weights[i])),
+            cell_coveredness: lerp(w.cell_coveredness, s.cell_coveredness),
+            height: lerp(w.height, s.height),
+            height_upper_half: lerp(w.height_upper_half, s.height_upper_half),
+            height_upper_quarter: lerp(w.height_upper_quarter, s.height_upper_quarter),
+            bumpiness: lerp(w.bumpiness, s.bumpiness),
+            bumpiness_sq: lerp(w.bumpiness_sq, s.bumpiness_sq),
+            row_transitions: lerp(w.row_transitions, s.row_transitions),
+            well_depth: lerp(w.well_depth, s.well_depth),
+            tsd_overhang: lerp(w.tsd_overhang, s.tsd_overhang),
+            four_wide_well: lerp(w.four_wide_well, s.four_wide_well),
+            tetris_ready: lerp(w.tetris_ready, s.tetris_ready),
+            spin_potential: lerp(w.spin_potential, s.spin_potential),
+            garbage_alignment: lerp(w.garbage_alignment, s.garbage_alignment),
+            pillar: lerp(w.pillar, s.pillar),
+            stack_volume: lerp(w.stack_volume, s.stack_volume),
         }
     }
 }
@@ -162,6 +225,214 @@ fn find_well(heights: &[usize; COL_NB]) -> (Option<usize>, i32) {
     (best_col, best_depth)
 }
 
+/// Surface flatness in (0, 1]: `1 / (1 + bumpiness)` over every adjacent
+/// column pair (no well exclusion). A perfectly flat surface is 1.0.
+pub fn flatness(board: &Board) -> f32 {
+    1.0 / (1.0 + surface_bumpiness(board) as f32)
+}
+
+/// Sum of adjacent column height differences, wells included.
+pub fn surface_bumpiness(board: &Board) -> u32 {
+    bumpiness(&column_heights(board), None).0 as u32
+}
+
+/// Per column, how far below the top of the visible field a piece can reach,
+/// tucks included: rows down to the lowest cell of the column that a single
+/// mino can get to from the top, moving down or sideways through empty cells
+/// but never up. Under an overhang that is open to the side this runs past the
+/// roof; a sealed pocket stops at it. Real pieces need more room than one
+/// mino, so this is an upper bound on tuck and spin reach. 0 for a column
+/// filled to the top.
+pub fn access_depth(board: &Board) -> [usize; COL_NB] {
+    let mut depth = [0usize; COL_NB];
+    let mut reached = FULL_ROW;
+    for y in (0..VISIBLE_HEIGHT).rev() {
+        let empty = !board.rows[y] & FULL_ROW;
+        reached &= empty;
+        loop {
+            let spread = reached | ((reached << 1 | reached >> 1) & empty);
+            if spread == reached {
+                break;
+            }
+            reached = spread;
+        }
+        for (x, d) in depth.iter_mut().enumerate() {
+            if reached >> x & 1 != 0 {
+                *d = VISIBLE_HEIGHT - y;
+            }
+        }
+    }
+    depth
+}
+
+/// Empty cells with a filled cell somewhere above them in the same column.
+pub fn count_holes(board: &Board) -> i32 {
+    holes_and_covered(board, &column_heights(board)).0
+}
+
+/// Holes per depth band: a hole's depth is how many rows its column's top
+/// cell sits above it (1 = directly under the surface); band `i` holds depth
+/// `i + 1`, and the last band also takes everything deeper.
+pub fn hole_depths(board: &Board) -> [u32; HOLE_DEPTH_BANDS] {
+    let mut bands = [0u32; HOLE_DEPTH_BANDS];
+    for x in 0..COL_NB {
+        let col = board.cols[x];
+        let h = board.column_height(x);
+        let mut open = !col & ((1u64 << h) - 1);
+        while open != 0 {
+            let y = open.trailing_zeros() as usize;
+            bands[(h - 1 - y).min(HOLE_DEPTH_BANDS) - 1] += 1;
+            open &= open - 1;
+        }
+    }
+    bands
+}
+
+/// Whether the board has digging to do rather than a clean stack: any hole
+/// (`count_holes`), or floor garbage (see `garbage_rows`) whose hole is
+/// covered. Holes lined up in one open column are a Tetris well, not garbage
+/// to dig; staggered holes bury each other and count.
+pub fn needs_downstack(board: &Board) -> bool {
+    count_holes(board) > 0 || garbage_alignment(board) < garbage_rows(board) as u32
+}
+
+/// Cells that were open sky in `before` (above their column's top) and are
+/// buried holes in `after`. Cleared lines shift rows down, so a placement that
+/// clears can report zero even when it lands high.
+pub fn newly_covered_holes(before: &Board, after: &Board) -> u32 {
+    let below_top = |h: usize| if h == 0 { 0 } else { (1u64 << h) - 1 };
+    let heights_before = column_heights(before);
+    let heights_after = column_heights(after);
+
+    (0..COL_NB)
+        .map(|x| {
+            let holes_after = !after.cols[x] & below_top(heights_after[x]);
+            (holes_after & !below_top(heights_before[x])).count_ones()
+        })
+        .sum()
+}
+
+/// Minimum depth below both neighbours for a column to count as a well.
+const MIN_WELL_DEPTH: i32 = 3;
+
+/// Column that is a clear Tetris-well candidate: the deepest single-column
+/// well, at least `MIN_WELL_DEPTH` rows below both neighbours (walls count as
+/// full height). Returns `None` if no column qualifies.
+pub fn detect_well_column(board: &Board) -> Option<usize> {
+    let heights = column_heights(board);
+    match find_well(&heights) {
+        (Some(x), depth) if depth >= MIN_WELL_DEPTH => Some(x),
+        _ => None,
+    }
+}
+
+/// Column heights after locking `mv` on a board with `heights`, updating only
+/// from the move's minos and the cleared rows. `cleared_rows` must be the
+/// board's `filled_row_mask()` taken after placing and before clearing; a wrong
+/// mask silently gives wrong heights. If a column's top cell sits in a cleared
+/// row above a hole, the hole isn't visible from heights alone and the
+/// result for that column is an upper bound.
+pub fn heights_after_move(
+    heights: &[usize; COL_NB],
+    mv: &Move,
+    cleared_rows: u64,
+) -> [usize; COL_NB] {
+    let mut out = *heights;
+    let pc = mv.cells();
+    let minos = std::iter::once((mv.x(), mv.y()))
+        .chain(pc.coords.iter().map(|c| (mv.x() + c.x as i32, mv.y() + c.y as i32)));
+    for (x, y) in minos {
+        if (0..COL_NB as i32).contains(&x) && y >= 0 {
+            let h = &mut out[x as usize];
+            *h = (*h).max(y as usize + 1);
+        }
+    }
+    if cleared_rows != 0 {
+        for h in out.iter_mut() {
+            *h -= (cleared_rows & ((1u64 << *h) - 1)).count_ones() as usize;
+        }
+    }
+    out
+}
+
+/// Consecutive rows from the floor that are full except for `well_column`.
+pub fn tetris_readiness(board: &Board, well_column: usize) -> u32 {
+    debug_assert!(well_column < COL_NB);
+    let ready_row = FULL_ROW & !(1u16 << well_column);
+    board.rows.iter().take_while(|&&row| row == ready_row).count() as u32
+}
+
+/// Garbage rows: the floor run of rows with exactly one hole.
+pub fn garbage_rows(board: &Board) -> usize {
+    board
+        .rows
+        .iter()
+        .take_while(|row| row.count_ones() == COL_NB as u32 - 1)
+        .count()
+}
+
+/// Garbage rows (see `garbage_rows`) whose hole has nothing above it in that
+/// column, i.e. can still be reached by a drop.
+/// Holes stacked in one column count in full; staggered holes bury each other.
+pub fn garbage_alignment(board: &Board) -> u32 {
+    let mut accessible = 0u32;
+    for (y, &row) in board.rows[..garbage_rows(board)].iter().enumerate() {
+        let hole = (!row & FULL_ROW).trailing_zeros() as usize;
+        if board.cols[hole] >> (y + 1) == 0 {
+            accessible += 1;
+        }
+    }
+    accessible
+}
+
+/// Rows a column must stand above both neighbours to count as a pillar in eval.
+const MIN_PILLAR_HEIGHT: usize = 3;
+
+/// Columns standing at least `min_overhang` (and at least one) rows above both
+/// neighbours. The inverse of a well; edge columns never count since the
+/// walls are treated as full height.
+pub fn pillar_count(board: &Board, min_overhang: usize) -> u32 {
+    let heights = column_heights(board);
+    pillars(&heights, min_overhang)
+}
+
+#[inline]
+fn pillars(heights: &[usize; COL_NB], min_overhang: usize) -> u32 {
+    heights
+        .windows(3)
+        .filter(|w| {
+            let rise = w[1].saturating_sub(w[0].max(w[2]));
+            rise > 0 && rise >= min_overhang
+        })
+        .count() as u32
+}
+
+/// Open T-slots by the 3-corner rule: a south-facing T footprint (pivot,
+/// both arms, stem) is empty and at least 3 of the pivot's diagonal corners
+/// are blocked. Cheap stand-in for running T movegen on every eval.
+fn count_t_slots(board: &Board, max_height: usize) -> i32 {
+    let mut count = 0i32;
+    for y in 1..=(max_height as i32).min(BOARD_HEIGHT as i32 - 2) {
+        for x in 1..(COL_NB as i32 - 1) {
+            let footprint_open = !board.obstructed(x, y)
+                && !board.obstructed(x - 1, y)
+                && !board.obstructed(x + 1, y)
+                && !board.obstructed(x, y - 1);
+            if !footprint_open {
+                continue;
+            }
+            let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+                .iter()
+                .filter(|(dx, dy)| board.obstructed(x + dx, y + dy))
+                .count();
+            if corners >= 3 {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
 #[inline]
 fn count_tsd_overhangs(board: &Board, heights: &[usize; COL_NB]) -> i32 {
     let mut count = 0i32;
@@ -238,7 +509,68 @@ fn four_wide_well_score(heights: &[usize; COL_NB]) -> f32 {
     score
 }
 
+/// Pluggable board scorer for search. `lines` is the clear count of the
+/// placement that produced `board`; the transposition table keys on both.
+pub trait Evaluator {
+    fn score(&self, board: &Board, lines: u8) -> f32;
+}
+
+impl Evaluator for EvalWeights {
+    fn score(&self, board: &Board, _lines: u8) -> f32 {
+        evaluate(board, self)
+    }
+}
+
+/// Weighted contribution of each eval term; `total()` equals `evaluate`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EvalBreakdown {
+    pub holes: f32,
+    pub cell_coveredness: f32,
+    pub height: f32,
+    pub height_upper_half: f32,
+    pub height_upper_quarter: f32,
+    pub bumpiness: f32,
+    pub bumpiness_sq: f32,
+    pub row_transitions: f32,
+    pub well_depth: f32,
+    pub tsd_overhang: f32,
+    pub four_wide_well: f32,
+    pub tetris_ready: f32,
+    pub spin_potential: f32,
+    pub garbage_alignment: f32,
+    pub pillar: f32,
+    pub stack_volume: f32,
+}
+
+impl EvalBreakdown {
+    /// Sum in the same order `evaluate` always used, so totals are bit-identical.
+    pub fn total(&self) -> f32 {
+        let mut score = 0.0f32;
+        score += self.holes;
+        score += self.cell_coveredness;
+        score += self.height;
+        score += self.height_upper_half;
+        score += self.height_upper_quarter;
+        score += self.bumpiness;
+        score += self.bumpiness_sq;
+        score += self.row_transitions;
+        score += self.well_depth;
+        score += self.tsd_overhang;
+        score += self.four_wide_well;
+        score += self.tetris_ready;
+        score += self.spin_potential;
+        score += self.garbage_alignment;
+        score += self.pillar;
+        score += self.stack_volume;
+        score
+    }
+}
+
 pub fn evaluate(board: &Board, weights: &EvalWeights) -> f32 {
+    evaluate_breakdown(board, weights).total()
+}
+
+pub fn evaluate_breakdown(board: &Board, weights: &EvalWeights) -> EvalBreakdown {
     let heights = column_heights(board);
     let max_h = heights.iter().copied().max().unwrap_or(0);
 
@@ -247,38 +579,81 @@ pub fn evaluate(board: &Board, weights: &EvalWeights) -> f32 {
     let (bump, bump_sq) = bumpiness(&heights, well_col);
     let r_transitions = row_transitions(board, max_h);
 
-    let mut score = 0.0f32;
-
-    score += weights.holes * holes as f32;
-    score += weights.cell_coveredness * covered as f32;
+    let hole_term = if weights.hole_weights.iter().all(|&w| w == 0.0) {
+        weights.holes * holes as f32
+    } else {
+        let bands = hole_depths(board);
+        (0..HOLE_DEPTH_BANDS)
+            .map(|i| weights.hole_weights[i] * bands[i] as f32)
+            .sum()
+    };
 
-    score += weights.height * max_h as f32;
+    let mut terms = EvalBreakdown {
+        holes: hole_term,
+        cell_coveredness: weights.cell_coveredness * covered as f32,
+        height: weights.height * max_h as f32,
+        bumpiness: weights.bumpiness * bump as f32,
+        bumpiness_sq: weights.bumpiness_sq * bump_sq as f32,
+        row_transitions: weights.row_transitions * r_transitions as f32,
+        well_depth: weights.well_depth * well_depth as f32,
+        ..EvalBreakdown::default()
+    };
     if max_h > 10 {
-        score += weights.height_upper_half * (max_h - 10) as f32;
+        terms.height_upper_half = weights.height_upper_half * (max_h - 10) as f32;
     }
     if max_h > 15 {
-        score += weights.height_upper_quarter * (max_h - 15) as f32;
+        terms.height_upper_quarter = weights.height_upper_quarter * (max_h - 15) as f32;
     }
 
-    score += weights.bumpiness * bump as f32;
-    score += weights.bumpiness_sq * bump_sq as f32;
-    score += weights.row_transitions * r_transitions as f32;
-
-    score += weights.well_depth * well_depth as f32;
-
     let tsd_count = count_tsd_overhangs(board, &heights);
-    score += weights.tsd_overhang * tsd_count as f32;
+    terms.tsd_overhang = weights.tsd_overhang * tsd_count as f32;
 
     let four_wide = four_wide_well_score(&heights);
-    score += weights.four_wide_well * four_wide;
+    terms.four_wide_well = weights.four_wide_well * four_wide;
 
-    score
+    if let Some(wc) = well_col {
+        terms.tetris_ready = weights.tetris_ready * tetris_readiness(board, wc) as f32;
+    }
+
+    // scan only when weighted; keeps the default eval path unchanged
+    if weights.spin_potential != 0.0 {
+        terms.spin_potential = weights.spin_potential * count_t_slots(board, max_h) as f32;
+    }
+    if weights.garbage_alignment != 0.0 {
+        terms.garbage_alignment = weights.garbage_alignment * garbage_alignment(board) as f32;
+    }
+    if weights.pillar != 0.0 {
+        terms.pillar = weights.pillar * pillars(&heights, MIN_PILLAR_HEIGHT) as f32;
+    }
+    // overall stack mass: filled cells, independent of max height and holes
+    terms.stack_volume = weights.stack_volume * board.total_cells() as f32;
+
+    terms
+}
+
+/// Copy of `board` after receiving `lines` rows of clean garbage with the
+/// hole at `hole_column`.
+pub fn project_garbage(board: &Board, lines: u8, hole_column: usize) -> Board {
+    let mut projected = board.clone();
+    if lines > 0 {
+        projected.spawn_garbage(lines as i32, hole_column as i32);
+    }
+    projected
+}
+
+/// Board-shape eval after receiving `lines` of garbage at `hole_column`.
+pub fn eval_after_garbage(
+    board: &Board,
+    lines: u8,
+    hole_column: usize,
+    weights: &EvalWeights,
+) -> f32 {
+    evaluate(&project_garbage(board, lines, hole_column), weights)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::{Board, FULL_ROW};
 
     #[test]
     fn test_empty_board_eval() {
@@ -351,4 +726,427 @@ mod tests {
         assert_eq!(well_col, Some(9));
         assert_eq!(well_depth, 4);
     }
+
+    fn board_from_heights(heights: &[usize; COL_NB]) -> Board {
+        let mut board = Board::new();
+        for (x, &h) in heights.iter().enumerate() {
+            board.fill_column(x, h);
+        }
+        board
+    }
+
+    #[test]
+    fn test_detect_well_column_right() {
+        let mut heights = [5usize; COL_NB];
+        heights[9] = 0;
+        assert_eq!(detect_well_column(&board_from_heights(&heights)), Some(9));
+    }
+
+    #[test]
+    fn test_detect_well_column_left() {
+        let mut heights = [4usize; COL_NB];
+        heights[0] = 1;
+        assert_eq!(detect_well_column(&board_from_heights(&heights)), Some(0));
+    }
+
+    #[test]
+    fn test_detect_well_column_flat_or_shallow() {
+        assert_eq!(detect_well_column(&board_from_heights(&[4; COL_NB])), None);
+        assert_eq!(detect_well_column(&Board::new()), None);
+
+        // 2-deep dip is not a clear well
+        let mut heights = [4usize; COL_NB];
+        heights[5] = 2;
+        assert_eq!(detect_well_column(&board_from_heights(&heights)), None);
+    }
+
+    #[test]
+    fn test_project_garbage_shifts_board_up() {
+        let mut heights = [2usize; COL_NB];
+        heights[9] = 0;
+        let board = board_from_heights(&heights);
+        let projected = project_garbage(&board, 3, 4);
+
+        assert_eq!(projected.height(), 5);
+        assert_eq!(projected.rows[0], FULL_ROW & !(1 << 4));
+        assert_eq!(projected.rows[3], board.rows[0]);
+        assert_eq!(project_garbage(&board, 0, 4).rows, board.rows);
+    }
+
+    #[test]
+    fn test_eval_after_garbage_under_hole_is_worse() {
+        // 3-high stack, open well at x=9, covered hole at x=2 on row 0
+        let mut heights = [3usize; COL_NB];
+        heights[9] = 0;
+        let mut board = board_from_heights(&heights);
+        board.rows[0] &= !(1 << 2);
+        board.cols[2] &= !1;
+
+        let weights = EvalWeights::default();
+        let under_hole = eval_after_garbage(&board, 2, 2, &weights);
+        let under_well = eval_after_garbage(&board, 2, 9, &weights);
+        assert!(
+            under_hole < under_well,
+            "garbage under a covered column ({}) should score below the open well ({})",
+            under_hole,
+            under_well
+        );
+    }
+
+    #[test]
+    fn test_tetris_readiness_counts_ready_rows() {
+        let mut heights = [3usize; COL_NB];
+        heights[9] = 0;
+        let board = board_from_heights(&heights);
+        assert_eq!(tetris_readiness(&board, 9), 3);
+        assert_eq!(tetris_readiness(&board, 0), 0);
+
+        let weights = EvalWeights {
+            tetris_ready: 2.0,
+            ..EvalWeights::default()
+        };
+        let base = evaluate(&board, &EvalWeights::default());
+        assert!((evaluate(&board, &weights) - base - 6.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_heights_after_move_matches_recompute() {
+        let mut heights = [4usize; COL_NB];
+        heights[0] = 6;
+        heights[9] = 0;
+        let mut board = board_from_heights(&heights);
+
+        let mv = [Rotation::East, Rotation::West]
+            .into_iter()
+            .flat_map(|r| (0..6).map(move |y| Move::new(Piece::I, r, 9, y, false)))
+            .find(|m| board.legal_lock_placement(m))
+            .unwrap_or_else(|| panic!("vertical I fits the well"));
+        let mut placed = board.clone();
+        placed.place(&mv);
+        let mask = placed.filled_row_mask();
+        assert_eq!(mask.count_ones(), 4);
+
+        let incremental = heights_after_move(&column_heights(&board), &mv, mask);
+        board.do_move(&mv);
+        assert_eq!(incremental, column_heights(&board));
+        assert_eq!(incremental[0], 2);
+        assert_eq!(incremental[5], 0);
+
+        // no clear: only the touched columns grow
+        let o = Move::new(Piece::O, Rotation::North, 4, 0, false);
+        let flat = heights_after_move(&[0; COL_NB], &o, 0);
+        let mut empty = Board::new();
+        empty.do_move(&o);
+        assert_eq!(flat, column_heights(&empty));
+    }
+
+    #[test]
+    fn test_tetris_readiness_blocked_row_breaks_count() {
+        let mut heights = [4usize; COL_NB];
+        heights[9] = 0;
+        let mut board = board_from_heights(&heights);
+        // plug the well on row 1
+        board.rows[1] |= 1 << 9;
+        board.cols[9] |= 1 << 1;
+        assert_eq!(tetris_readiness(&board, 9), 1);
+    }
+
+    #[test]
+    fn test_bumpiness_sq_punishes_spike_over_slope() {
+        // equal linear bumpiness (8), no wells: squared sums are 32 vs 10
+        let spike = board_from_heights(&[0, 0, 0, 0, 4, 0, 0, 0, 0, 0]);
+        let slope = board_from_heights(&[0, 0, 1, 2, 3, 4, 3, 2, 0, 0]);
+
+        let base = EvalWeights {
+            bumpiness: 0.0,
+            bumpiness_sq: 0.0,
+            ..EvalWeights::default()
+        };
+        let term = |board: &Board, w: EvalWeights| evaluate(board, &w) - evaluate(board, &base);
+        let linear = || EvalWeights {
+            bumpiness: -0.3,
+            ..base.clone()
+        };
+        let squared = || EvalWeights {
+            bumpiness_sq: -0.1,
+            ..base.clone()
+        };
+
+        assert!((term(&spike, linear()) - term(&slope, linear())).abs() < 1e-4);
+        assert!((term(&spike, squared()) + 3.2).abs() < 1e-4);
+        assert!((term(&slope, squared()) + 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_evaluate_breakdown_total_matches_evaluate() {
+        let weights = EvalWeights {
+            tetris_ready: 1.0,
+            ..EvalWeights::default()
+        };
+        let mut heights = [12usize; COL_NB];
+        heights[9] = 0;
+        heights[3] = 16;
+        let mut board = board_from_heights(&heights);
+        board.rows[4] &= !(1 << 5);
+        board.cols[5] &= !(1 << 4);
+
+        let terms = evaluate_breakdown(&board, &weights);
+        assert_eq!(terms.total(), evaluate(&board, &weights));
+        assert!(terms.holes < 0.0);
+        assert!(terms.height_upper_quarter < 0.0);
+        assert!(terms.tetris_ready > 0.0);
+        assert_eq!(evaluate_breakdown(&Board::new(), &weights).total(), 0.0);
+    }
+
+    #[test]
+    fn test_flatness_flat_boards_are_one() {
+        assert_eq!(flatness(&Board::new()), 1.0);
+        assert_eq!(flatness(&board_from_heights(&[3; COL_NB])), 1.0);
+
+        let mut step = [2usize; COL_NB];
+        step[9] = 3;
+        assert_eq!(flatness(&board_from_heights(&step)), 0.5);
+    }
+
+    #[test]
+    fn test_flatness_jagged_board_near_zero() {
+        let jagged = board_from_heights(&[0, 8, 0, 8, 0, 8, 0, 8, 0, 8]);
+        assert!(flatness(&jagged) < 0.02);
+    }
+
+    #[test]
+    fn test_newly_covered_holes_counts_fresh_overhang() {
+        // x=3 is two rows lower than its neighbours; a flat I on top buries it
+        let mut heights = [2usize; COL_NB];
+        heights[3] = 0;
+        let before = board_from_heights(&heights);
+        let mut after = before.clone();
+        after.do_move(&Move::new(Piece::I, Rotation::North, 3, 2, false));
+
+        assert_eq!(newly_covered_holes(&before, &after), 2);
+        // already-buried holes are not counted again
+        let mut later = after.clone();
+        later.do_move(&Move::new(Piece::O, Rotation::North, 3, 3, false));
+        assert_eq!(newly_covered_holes(&after, &later), 0);
+    }
+
+    #[test]
+    fn test_newly_covered_holes_zero_after_clear() {
+        // one-row gap at x=0..3; the I fills it and clears the row
+        let mut heights = [1usize; COL_NB];
+        for h in heights.iter_mut().take(4) {
+            *h = 0;
+        }
+        let before = board_from_heights(&heights);
+        let mut after = before.clone();
+        assert_eq!(after.do_move(&Move::new(Piece::I, Rotation::North, 1, 0, false)), 1);
+        assert_eq!(newly_covered_holes(&before, &after), 0);
+    }
+
+    fn tsd_notch_board() -> Board {
+        // row 2: overhang at x=3; row 1: open x=3..5; row 0: open x=4
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[0] = FULL_ROW & !(1 << 4);
+        rows[1] = FULL_ROW & !(0b111 << 3);
+        rows[2] = 1 << 3;
+        Board::from(&rows)
+    }
+
+    #[test]
+    fn test_spin_potential_rewards_open_tsd_notch() {
+        let weights = EvalWeights {
+            spin_potential: 3.0,
+            ..EvalWeights::default()
+        };
+        let notch = evaluate_breakdown(&tsd_notch_board(), &weights);
+        let flat = evaluate_breakdown(&board_from_heights(&[2; COL_NB]), &weights);
+        assert_eq!(notch.spin_potential, 3.0);
+        assert_eq!(flat.spin_potential, 0.0);
+        assert_eq!(evaluate_breakdown(&Board::new(), &weights).spin_potential, 0.0);
+
+        // default weight leaves scoring untouched
+        let off = evaluate_breakdown(&tsd_notch_board(), &EvalWeights::default());
+        assert_eq!(off.spin_potential, 0.0);
+    }
+
+    #[test]
+    fn test_garbage_alignment_aligned_vs_staggered() {
+        let hole_rows = |holes: &[usize]| {
+            let mut rows = [0u16; BOARD_HEIGHT];
+            for (y, &x) in holes.iter().enumerate() {
+                rows[y] = FULL_ROW & !(1 << x);
+            }
+            Board::from(&rows)
+        };
+        let aligned = hole_rows(&[3, 3, 3, 3]);
+        let staggered = hole_rows(&[3, 6, 1, 8]);
+        assert_eq!(garbage_alignment(&aligned), 4);
+        assert_eq!(garbage_alignment(&staggered), 1);
+        assert_eq!(garbage_alignment(&Board::new()), 0);
+
+        // a block over the well buries every hole under it
+        let mut capped = aligned.clone();
+        capped.rows[4] = 1 << 3;
+        capped.cols[3] |= 1 << 4;
+        assert_eq!(garbage_alignment(&capped), 0);
+
+        let weights = EvalWeights {
+            garbage_alignment: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(evaluate_breakdown(&aligned, &weights).garbage_alignment, 6.0);
+        assert_eq!(evaluate_breakdown(&staggered, &weights).garbage_alignment, 1.5);
+        assert_eq!(evaluate_breakdown(&aligned, &EvalWeights::default()).garbage_alignment, 0.0);
+    }
+
+    #[test]
+    fn test_needs_downstack_clean_vs_buried() {
+        assert!(!needs_downstack(&Board::new()));
+        assert!(!needs_downstack(&board_from_heights(&[0, 4, 4, 2, 1, 5, 3, 3, 2, 0])));
+
+        // garbage lined up under an open column is just a well
+        let mut rows = [0u16; BOARD_HEIGHT];
+        rows[..4].fill(FULL_ROW & !(1 << 2));
+        assert_eq!(garbage_rows(&Board::from(&rows)), 4);
+        assert!(!needs_downstack(&Board::from(&rows)));
+
+        // a cap over the column buries all four
+        rows[4] = 1 << 2;
+        assert!(needs_downstack(&Board::from(&rows)));
+        // staggered garbage buries the lower holes
+        let mut staggered = [0u16; BOARD_HEIGHT];
+        staggered[0] = FULL_ROW & !1;
+        staggered[1] = FULL_ROW & !(1 << 5);
+        assert!(needs_downstack(&Board::from(&staggered)));
+    }
+
+    #[test]
+    fn test_needs_downstack_false_for_tetris_ready_stack() {
+        let mut heights = [6usize; COL_NB];
+        heights[9] = 0;
+        let board = board_from_heights(&heights);
+        assert_eq!(tetris_readiness(&board, 9), 6);
+        assert!(!needs_downstack(&board));
+    }
+
+    #[test]
+    fn test_hole_weights_penalize_deep_holes_more() {
+        let column = |cells: &[usize]| {
+            let mut rows = [0u16; BOARD_HEIGHT];
+            for &y in cells {
+                rows[y] = 1;
+            }
+            Board::from(&rows)
+        };
+        // one hole each in column 0: under four cells vs right under the top
+        let deep = column(&[1, 2, 3, 4]);
+        let shallow = column(&[0, 1, 2, 3, 5]);
+        assert_eq!(hole_depths(&deep), [0, 0, 0, 1]);
+        assert_eq!(hole_depths(&shallow), [1, 0, 0, 0]);
+        // depths 1, 2, 3 just under the top; 4, 5, 6 and 8 share the last band
+        assert_eq!(hole_depths(&column(&[0, 2, 9])), [1, 1, 1, 4]);
+
+        let banded = EvalWeights {
+            hole_weights: [-1.0, -2.0, -4.0, -8.0],
+            ..Default::default()
+        };
+        let deep_term = evaluate_breakdown(&deep, &banded).holes;
+        let shallow_term = evaluate_breakdown(&shallow, &banded).holes;
+        assert_eq!(deep_term, -8.0);
+        assert_eq!(shallow_term, -1.0);
+
+        // scalar fallback: every hole costs the same
+        let scalar = EvalWeights::default();
+        assert_eq!(
+            evaluate_breakdown(&deep, &scalar).holes,
+            evaluate_breakdown(&shallow, &scalar).holes
+        );
+    }
+
+    #[test]
+    fn test_stack_volume_penalizes_mass_at_equal_height() {
+        let mut tall_spike = [0; COL_NB];
+        tall_spike[0] = 6;
+        let mut tall_block = [4; COL_NB];
+        tall_block[0] = 6;
+        let (spike, block) = (board_from_heights(&tall_spike), board_from_heights(&tall_block));
+        assert_eq!(spike.height(), block.height());
+
+        let weights = EvalWeights {
+            stack_volume: -0.5,
+            ..Default::default()
+        };
+        let spike_terms = evaluate_breakdown(&spike, &weights);
+        let block_terms = evaluate_breakdown(&block, &weights);
+        assert_eq!(spike_terms.stack_volume, -3.0);
+        assert_eq!(block_terms.stack_volume, -21.0);
+        assert!(block_terms.stack_volume < spike_terms.stack_volume);
+        assert_eq!(evaluate_breakdown(&block, &EvalWeights::default()).stack_volume, 0.0);
+    }
+
+    #[test]
+    fn test_access_depth_clean_vs_capped() {
+        let mut board = board_from_heights(&[4, 0, 4, 4, 4, 4, 4, 4, 4, 4]);
+        let depth = access_depth(&board);
+        assert_eq!(depth[1], VISIBLE_HEIGHT);
+        assert_eq!(depth[0], VISIBLE_HEIGHT - 4);
+
+        // roof over column 1 at row 5, open to the side at row 4: a tuck
+        // under it reaches the floor
+        board.rows[5] |= 1 << 1;
+        board.cols[1] |= 1 << 5;
+        assert_eq!(count_holes(&board), 5);
+        assert_eq!(access_depth(&board)[1], VISIBLE_HEIGHT);
+
+        // raising the neighbours seals the pocket
+        let mut sealed = board_from_heights(&[6, 0, 6, 4, 4, 4, 4, 4, 4, 4]);
+        sealed.rows[5] |= 1 << 1;
+        sealed.cols[1] |= 1 << 5;
+        assert_eq!(access_depth(&sealed)[1], VISIBLE_HEIGHT - 6);
+        assert_eq!(access_depth(&Board::new()), [VISIBLE_HEIGHT; COL_NB]);
+    }
+
+    #[test]
+    fn test_pillar_spike_vs_edge_well() {
+        let mut spike = [2; COL_NB];
+        spike[4] = 6;
+        let spike = board_from_heights(&spike);
+        assert_eq!(pillar_count(&spike, 3), 1);
+        assert_eq!(pillar_count(&spike, 4), 1);
+        assert_eq!(pillar_count(&spike, 5), 0);
+
+        // a low edge column is a well; nothing rises above its neighbours
+        let mut well = [6; COL_NB];
+        well[9] = 0;
+        let well = board_from_heights(&well);
+        assert_eq!(pillar_count(&well, 1), 0);
+        assert_eq!(pillar_count(&Board::new(), 0), 0);
+
+        // a tall edge column leans on the wall
+        let mut edge = [0; COL_NB];
+        edge[0] = 8;
+        assert_eq!(pillar_count(&board_from_heights(&edge), 1), 0);
+
+        let weights = EvalWeights {
+            pillar: -2.0,
+            ..Default::default()
+        };
+        assert_eq!(evaluate_breakdown(&spike, &weights).pillar, -2.0);
+        assert_eq!(evaluate_breakdown(&well, &weights).pillar, 0.0);
+        assert_eq!(evaluate_breakdown(&spike, &EvalWeights::default()).pillar, 0.0);
+    }
+
+    #[test]
+    fn test_scaled_weights_endpoints() {
+        let strong = EvalWeights::scaled(1.0);
+        let default = EvalWeights::default();
+        assert_eq!(strong.holes, default.holes);
+        assert_eq!(strong.tsd_overhang, default.tsd_overhang);
+        assert_eq!(EvalWeights::scaled(7.0).holes, default.holes);
+
+        let weak = EvalWeights::scaled(0.0);
+        assert!(weak.holes > default.holes);
+        let mid = EvalWeights::scaled(0.5).holes;
+        assert!(mid < weak.holes && mid > default.holes);
+    }
 }
@@ -1,4 +1,5 @@
 // gen.rs -- 1:1 port of gen.hpp
+use crate::board::Board;
 use crate::header::*;
 
 pub(crate) const SPAWN_COL: usize = 4;
@@ -210,14 +211,90 @@ pub(crate) fn kick_180_index(p: Piece) -> usize {
     (p == Piece::I) as usize
 }
 
+// -- KickSystem --
+// Rotation system used for wall kicks.
+//   SrsPlus = TETR.IO SRS+ (symmetric I kicks, 6-offset 180 table)
+//   Srs     = guideline SRS (classic 5-offset tables, 180 only in place)
+//   None    = basic rotation only, no kicks and no 180 rotation
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum KickSystem {
+    #[default]
+    SrsPlus,
+    Srs,
+    None,
+}
+
+impl KickSystem {
+    // number of CW/CCW offsets to try (first offset is the unkicked rotation)
+    pub(crate) const fn kick_count(self) -> usize {
+        match self {
+            KickSystem::SrsPlus | KickSystem::Srs => 5,
+            KickSystem::None => 1,
+        }
+    }
+
+    // number of 180 offsets to try (first offset is the unkicked rotation);
+    // 0 disables 180 rotation
+    pub(crate) const fn kick_180_count(self) -> usize {
+        match self {
+            KickSystem::SrsPlus => 6,
+            KickSystem::Srs => 1,
+            KickSystem::None => 0,
+        }
+    }
+
+    pub(crate) fn kick_index(self, p: Piece) -> usize {
+        kick_index(p, self == KickSystem::SrsPlus)
+    }
+}
+
+/// Kick offsets tried, in order, when rotating `piece` from `from` to `to`
+/// under `system`. Returns an empty slice when the rotation isn't allowed
+/// (180 under `None`, or `from == to`); a 180 under `Srs` has no kicks, only
+/// the unkicked rotation.
+pub fn get_kicks_for(
+    system: KickSystem,
+    piece: Piece,
+    from: Rotation,
+    to: Rotation,
+) -> &'static [Coordinates] {
+    if from == to {
+        return &[];
+    }
+    let fi = from as usize;
+    if to == rotate(Direction::Flip, from) {
+        let n = system.kick_180_count();
+        return &KICKS_180[kick_180_index(piece)][fi][..n];
+    }
+    let d = if to == rotate(Direction::Cw, from) {
+        Direction::Cw
+    } else {
+        Direction::Ccw
+    };
+    &KICKS[system.kick_index(piece)][d as usize][fi][..system.kick_count()]
+}
+
 // -- CollisionMap --
 // C++ CollisionMap<p>: board[COL_NB][canonicalSize] of Bitboard
 // Each entry is OR of column bitboards shifted by piece cell offsets
-pub(crate) struct CollisionMap {
+/// Per-column, per-rotation blocked pivot rows for one piece on one board.
+/// Build with `from_board`; see `movegen::generate_moves_from_map`.
+#[derive(Clone, Copy)]
+pub struct CollisionMap {
     pub(crate) board: [[Bitboard; 4]; COL_NB], // max 4 canonical rotations
+    piece: Piece,
 }
 
 impl CollisionMap {
+    /// Map of `p` on `board`. Only valid for that piece and board.
+    pub fn from_board(board: &Board, p: Piece) -> Self {
+        Self::new(&board.compute_cols(), p)
+    }
+
+    pub fn piece(&self) -> Piece {
+        self.piece
+    }
+
     pub(crate) fn new(cols: &[Bitboard; COL_NB], p: Piece) -> Self {
         let cs = canonical_size(p);
         let mut board = [[0u64; 4]; COL_NB];
@@ -244,7 +321,7 @@ impl CollisionMap {
             }
         }
 
-        CollisionMap { board }
+        CollisionMap { board, piece: p }
     }
 
     pub(crate) fn get(&self, x: usize, r: Rotation) -> Bitboard {
@@ -364,6 +441,37 @@ mod tests {
         assert!(in_bounds(Piece::T, Rotation::North, 1));
     }
 
+    #[test]
+    fn test_srs_lacks_180_kicks() {
+        let plus = get_kicks_for(KickSystem::SrsPlus, Piece::T, Rotation::North, Rotation::South);
+        let srs = get_kicks_for(KickSystem::Srs, Piece::T, Rotation::North, Rotation::South);
+        assert_eq!(plus.len(), 6);
+        // in-place 180 only
+        assert_eq!(srs, &plus[..1]);
+        assert_eq!(srs[0], Coordinates::new(0, 0));
+        assert!(get_kicks_for(KickSystem::None, Piece::T, Rotation::North, Rotation::South).is_empty());
+    }
+
+    #[test]
+    fn test_get_kicks_for_cw_ccw() {
+        for system in [KickSystem::SrsPlus, KickSystem::Srs] {
+            let cw = get_kicks_for(system, Piece::L, Rotation::North, Rotation::East);
+            assert_eq!(cw, &KICKS[0][Direction::Cw as usize][0][..]);
+            let ccw = get_kicks_for(system, Piece::L, Rotation::North, Rotation::West);
+            assert_eq!(ccw, &KICKS[0][Direction::Ccw as usize][0][..]);
+        }
+        // I kicks differ between SRS and SRS+
+        let plus = get_kicks_for(KickSystem::SrsPlus, Piece::I, Rotation::North, Rotation::East);
+        let srs = get_kicks_for(KickSystem::Srs, Piece::I, Rotation::North, Rotation::East);
+        assert_eq!(plus.len(), 5);
+        assert_eq!(srs.len(), 5);
+        assert_ne!(plus, srs);
+        // None only keeps the unkicked rotation
+        let none = get_kicks_for(KickSystem::None, Piece::I, Rotation::North, Rotation::East);
+        assert_eq!(none, &srs[..1]);
+        assert!(get_kicks_for(KickSystem::Srs, Piece::T, Rotation::East, Rotation::East).is_empty());
+    }
+
     #[test]
     fn test_kick_tables_size() {
         assert_eq!(KICKS[0][0].len(), ROTATION_NB);
@@ -5,7 +5,9 @@ pub type Bitboard = u64;
 pub const COL_NB: usize = 10;
 pub const ROW_NB: usize = 40;
 
+/// Serialized by variant name (`"I"`, `"O"`, ...); names are stable.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Piece {
     I = 0,
@@ -30,6 +32,20 @@ impl Piece {
             _ => panic!("invalid Piece discriminant"),
         }
     }
+
+    /// Piece from its letter (`'I'`, `'o'`, ...), case-insensitive.
+    pub const fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'I' => Some(Piece::I),
+            'O' => Some(Piece::O),
+            'T' => Some(Piece::T),
+            'L' => Some(Piece::L),
+            'J' => Some(Piece::J),
+            'S' => Some(Piece::S),
+            'Z' => Some(Piece::Z),
+            _ => None,
+        }
+    }
 }
 
 pub const PIECE_NB: usize = 7;
@@ -47,7 +63,9 @@ pub const ALL_PIECES: [Piece; PIECE_NB] = [
     Piece::Z,
 ];
 
+/// Serialized by variant name (`"North"`, `"East"`, ...); names are stable.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Rotation {
     North = 0,
@@ -77,7 +95,9 @@ pub const ALL_ROTATIONS: [Rotation; ROTATION_NB] = [
     Rotation::West,
 ];
 
+/// Serialized by variant name (`"NoSpin"`, `"Mini"`, `"Full"`); names are stable.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum SpinType {
     NoSpin = 0,
@@ -166,7 +186,11 @@ impl std::ops::SubAssign for Coordinates {
 // -- Move --
 // C++ layout: y:6, x:4, piece:3, rotation:2, spin:1 = 16 bits
 
+/// Serialized as the packed 16-bit value above, so a `Vec<Move>` is a
+/// plain JSON array of integers. Deserializing goes through `TryFrom<u16>`.
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u16", into = "u16"))]
 pub struct Move {
     data: u16,
 }
@@ -241,6 +265,83 @@ impl Move {
     pub fn cells(self) -> PieceCoordinates {
         piece_table(self.piece(), self.rotation())
     }
+
+    /// True if any mino sits at or above `board::VISIBLE_HEIGHT`.
+    pub fn tops_out(self) -> bool {
+        let top = self.cells().coords.iter().map(|c| c.y as i32).max().unwrap_or(0).max(0);
+        self.y() + top >= crate::board::VISIBLE_HEIGHT as i32
+    }
+
+    /// True if any mino lands in column `x`.
+    pub fn touches_column(self, x: usize) -> bool {
+        let x = x as i32;
+        self.x() == x || self.cells().coords.iter().any(|c| self.x() + c.x as i32 == x)
+    }
+
+    /// Top-left cell of the placement's bounding box in TETR.IO-style
+    /// screen coordinates: x from the left wall, y counted down from the top
+    /// visible row (y = 0), so hidden-buffer rows are negative. Spin and the
+    /// pivot choice are lost; `from_absolute` undoes the mapping.
+    pub fn to_absolute(self) -> (i8, i8) {
+        let (left, top) = box_corner(self.piece(), self.rotation());
+        let x = self.x() + left;
+        let y = VISIBLE_TOP_ROW - (self.y() + top);
+        (x as i8, y as i8)
+    }
+
+    /// Inverse of `to_absolute` for `piece` in `rotation`; always `NoSpin`.
+    pub fn from_absolute(piece: Piece, rotation: Rotation, x: i8, y: i8) -> Self {
+        let (left, top) = box_corner(piece, rotation);
+        Move::new(
+            piece,
+            rotation,
+            x as i32 - left,
+            VISIBLE_TOP_ROW - y as i32 - top,
+            false,
+        )
+    }
+}
+
+// row index of the top visible row (`board::VISIBLE_HEIGHT - 1`)
+const VISIBLE_TOP_ROW: i32 = crate::board::VISIBLE_HEIGHT as i32 - 1;
+
+// (leftmost x, topmost y) offset of a piece's cells from its pivot
+fn box_corner(piece: Piece, rotation: Rotation) -> (i32, i32) {
+    let cells = piece_table(piece, rotation);
+    cells
+        .coords
+        .iter()
+        .fold((0, 0), |(left, top), c| (left.min(c.x as i32), top.max(c.y as i32)))
+}
+
+/// Every piece and rotation bit pattern decodes (piece 7 is the T-spin
+/// marker), so only an x or y off the board is rejected.
+impl TryFrom<u16> for Move {
+    type Error = String;
+
+    fn try_from(data: u16) -> Result<Self, Self::Error> {
+        let m = Move { data };
+        if m.x() >= COL_NB as i32 {
+            return Err(format!("move {data:#06x}: x {} off the board", m.x()));
+        }
+        if m.y() >= crate::board::BOARD_HEIGHT as i32 {
+            return Err(format!("move {data:#06x}: y {} off the board", m.y()));
+        }
+        Ok(m)
+    }
+}
+
+impl From<Move> for u16 {
+    fn from(m: Move) -> u16 {
+        m.raw()
+    }
+}
+
+impl Default for Move {
+    /// `Move::none()`.
+    fn default() -> Self {
+        Self::none()
+    }
 }
 
 impl std::fmt::Debug for Move {
@@ -391,6 +492,27 @@ mod tests {
         assert_eq!((pc[2].x, pc[2].y), (0, -2));
     }
 
+    #[test]
+    fn test_move_absolute_coordinates() {
+        // flat I on the floor: cells x 3..=6 in the bottom row
+        let i = Move::new(Piece::I, Rotation::North, 4, 0, false);
+        assert_eq!(i.to_absolute(), (3, 19));
+        // T North's nub is above the pivot, so the box starts a row higher
+        let t = Move::new(Piece::T, Rotation::North, 4, 0, false);
+        assert_eq!(t.to_absolute(), (3, 18));
+        // O pivot is its bottom-left cell
+        let o = Move::new(Piece::O, Rotation::North, 0, 19, false);
+        assert_eq!(o.to_absolute(), (0, -1));
+        // vertical I East: pivot second from the top
+        let east = Move::new(Piece::I, Rotation::East, 9, 2, false);
+        assert_eq!(east.to_absolute(), (9, 16));
+
+        for m in [i, t, o, east, Move::new(Piece::Z, Rotation::West, 5, 7, false)] {
+            let (x, y) = m.to_absolute();
+            assert_eq!(Move::from_absolute(m.piece(), m.rotation(), x, y), m);
+        }
+    }
+
     #[test]
     fn test_move_roundtrip() {
         let m = Move::new(Piece::T, Rotation::East, 5, 10, false);
@@ -426,4 +548,41 @@ mod tests {
         assert_eq!(bb(3), 8);
         assert_eq!(bb_low(3), 7);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_move_list_serde_round_trip() {
+        let moves = vec![
+            Move::new(Piece::I, Rotation::North, 4, 0, false),
+            Move::new_tspin(Rotation::South, 3, 1, true),
+            Move::new_allspin_mini(Piece::S, Rotation::East, 7, 2),
+        ];
+        let json = serde_json::to_string(&moves).unwrap_or_else(|e| panic!("{e}"));
+        let raws: Vec<String> = moves.iter().map(|m| m.raw().to_string()).collect();
+        assert_eq!(json, format!("[{}]", raws.join(",")));
+
+        let back: Vec<Move> = serde_json::from_str(&json).unwrap_or_else(|e| panic!("{e}"));
+        assert!(back == moves);
+
+        let enums = (Piece::Z, Rotation::West, SpinType::Mini);
+        let json = serde_json::to_string(&enums).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(json, r#"["Z","West","Mini"]"#);
+        let back: (Piece, Rotation, SpinType) =
+            serde_json::from_str(&json).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(back, enums);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_move_deserialize_rejects_off_board_fields() {
+        let raw = |x: u16, y: u16| (x << 6) | y;
+        for bad in [raw(COL_NB as u16, 0), raw(15, 3), raw(4, 40), raw(4, 63)] {
+            assert!(serde_json::from_str::<Move>(&bad.to_string()).is_err(), "{bad:#06x}");
+        }
+
+        // piece bits 7 is a T-spin, not a bad piece
+        let tspin = (TSPIN << 10) | raw(3, 1);
+        let m: Move = serde_json::from_str(&tspin.to_string()).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!((m.piece(), m.spin()), (Piece::T, SpinType::Mini));
+    }
 }
@@ -10,6 +10,7 @@ pub mod header;
 pub mod move_buffer;
 pub mod movegen;
 pub mod pathfinder;
+pub mod pc;
 pub mod perft;
 pub mod replay_validation;
 pub mod ruleset;
@@ -28,3 +29,6 @@ pub mod wasm;
 
 #[cfg(feature = "wasm")]
 pub mod wasm_board;
+
+#[cfg(feature = "wasm")]
+pub mod wasm_state;
@@ -46,6 +46,20 @@ impl MoveBuffer {
     pub fn iter(&self) -> std::slice::Iter<'_, Move> {
         self.as_slice().iter()
     }
+
+    /// Keep moves at index >= `start` only where `keep` returns true,
+    /// preserving order. Moves before `start` are left untouched.
+    pub fn retain_from(&mut self, start: usize, mut keep: impl FnMut(&Move) -> bool) {
+        let mut write = start;
+        for read in start..self.len {
+            let m = self.data[read];
+            if keep(&m) {
+                self.data[write] = m;
+                write += 1;
+            }
+        }
+        self.len = write;
+    }
 }
 
 impl Default for MoveBuffer {
@@ -1,15 +1,91 @@
 // movegen.rs -- 1:1 port of movegen.hpp + movegen.cpp
 // const generics mirror C++ template<Piece p1> specialization
-use crate::board::Board;
+use crate::board::{Board, BOARD_HEIGHT, FULL_ROW};
 use crate::default_ruleset::ACTIVE_RULES;
 use crate::gen::{
-    canonical_offset, canonical_r, canonical_size, group2, in_bounds, kick_180_index, kick_index,
-    rotate, CollisionMap, CollisionMap16, Direction, KICKS, KICKS_180, SPAWN_COL,
+    canonical_offset, canonical_r, canonical_size, group2, in_bounds, kick_180_index, rotate,
+    CollisionMap, CollisionMap16, Direction, KickSystem, KICKS, KICKS_180, SPAWN_COL,
 };
 use crate::header::*;
+use crate::ruleset::{Rules, SpinRule};
 
 pub use crate::move_buffer::{MoveBuffer, MoveList};
 
+/// Move generation options. `Default` follows `ACTIVE_RULES`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveGenConfig {
+    pub kicks: KickSystem,
+    /// Max rows a placement may sit below its natural (hard-drop from above)
+    /// position in the same column/rotation. `None` = unlimited soft drop.
+    pub max_soft_drop: Option<u8>,
+    /// Rows at or above this are off-limits: placements with any mino at
+    /// `y >= ceiling` are dropped. `BOARD_HEIGHT` = no limit.
+    pub ceiling: usize,
+    /// Under `TSpinOnly`, non-T pieces skip spin detection and lock as `NoSpin`.
+    pub spin_rule: SpinRule,
+}
+
+impl Default for MoveGenConfig {
+    fn default() -> Self {
+        Self::for_rules(&ACTIVE_RULES)
+    }
+}
+
+impl MoveGenConfig {
+    /// Kick system and spin rule implied by `rules`: SRS+ when `srs_plus`,
+    /// otherwise SRS (which still rotates 180 in place).
+    pub fn for_rules(rules: &Rules) -> Self {
+        Self {
+            kicks: if rules.srs_plus {
+                KickSystem::SrsPlus
+            } else {
+                KickSystem::Srs
+            },
+            max_soft_drop: None,
+            ceiling: BOARD_HEIGHT,
+            spin_rule: rules.spin_rule(),
+        }
+    }
+
+    #[inline]
+    fn allow_180(&self) -> bool {
+        ACTIVE_RULES.enable_180 && self.kicks.kick_180_count() > 0
+    }
+}
+
+// rows between m and where the same column/rotation lands when hard-dropped
+// from above the stack (0 for plain drops, >0 for tucks under overhangs)
+pub(crate) fn tuck_depth(cols: &[Bitboard; COL_NB], m: &Move) -> i32 {
+    let pc = m.cells();
+    let cells = [Coordinates::new(0, 0), pc[0], pc[1], pc[2]];
+    let natural = cells
+        .iter()
+        .map(|c| bitlen(cols[(m.x() + c.x as i32) as usize]) as i32 - c.y as i32)
+        .max()
+        .unwrap_or(0);
+    (natural - m.y()).max(0)
+}
+
+// Softdrop in one pass: every bit of `free` reached by sliding down one or
+// more rows from `from` without leaving `free`. Kogge-Stone fill, so six
+// steps whatever the drop length instead of one per row.
+#[inline(always)]
+fn fill_down(from: Bitboard, free: Bitboard) -> Bitboard {
+    let mut g = (from >> 1) & free;
+    let mut p = free;
+    g |= p & (g >> 1);
+    p &= p >> 1;
+    g |= p & (g >> 2);
+    p &= p >> 2;
+    g |= p & (g >> 4);
+    p &= p >> 4;
+    g |= p & (g >> 8);
+    p &= p >> 8;
+    g |= p & (g >> 16);
+    p &= p >> 16;
+    g | (p & (g >> 32))
+}
+
 // compile-time piece from const generic index — must match Piece enum discriminants
 #[inline(always)]
 const fn piece_from_index(p: usize) -> Piece {
@@ -33,6 +109,25 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
     slow: bool,
     force: bool,
     spin_map: Option<&[[Bitboard; 5]; COL_NB]>,
+    config: &MoveGenConfig,
+) {
+    let mut move_set = [[0u64; ROTATION_NB]; COL_NB];
+    generate_inner_into::<P, CHECK_SPIN>(cm, moves, slow, force, spin_map, config, &mut move_set);
+}
+
+// generate_inner leaving the lock positions in `move_set` (zeroed by the
+// caller): bit y of move_set[x][r] is a pivot at (x, y). Without CHECK_SPIN
+// r is the canonical rotation and each bit is one emitted move. Inlined so
+// generate_inner keeps its single out-of-line body.
+#[inline(always)]
+fn generate_inner_into<const P: usize, const CHECK_SPIN: bool>(
+    cm: &CollisionMap,
+    moves: &mut MoveBuffer,
+    slow: bool,
+    force: bool,
+    spin_map: Option<&[[Bitboard; 5]; COL_NB]>,
+    config: &MoveGenConfig,
+    move_set: &mut [[Bitboard; ROTATION_NB]; COL_NB],
 ) {
     let p = piece_from_index(P);
     let canonical_sz = canonical_size(p);
@@ -42,7 +137,6 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
     let mut remaining: Bitboard = 0;
     let mut to_search = [[0u64; ROTATION_NB]; COL_NB];
     let mut searched = [[0u64; ROTATION_NB]; COL_NB];
-    let mut move_set = [[0u64; ROTATION_NB]; COL_NB];
     // skip zeroing spin_set when CHECK_SPIN=false — all access is behind `if CHECK_SPIN` guards
     // matches Cobra's zero-size `spinSet[COL_NB][ROTATION_NB][checkSpin ? SPIN_NB : 0]`
     let mut spin_set: [[[u64; SPIN_NB]; ROTATION_NB]; COL_NB] =
@@ -111,6 +205,7 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
                     spin_set[x][ri][SpinType::NoSpin as usize] = surface;
                 } else {
                     moves.push(Move::new(p, r, x as i32, y as i32, false));
+                    move_set[x][ri] |= bb(y as i32);
                     total += popcount(!cm.get(x, r) & ((cm.get(x, r) << 1) | 1)) as i32 - 1;
                 }
             }
@@ -131,18 +226,11 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
         debug_assert!(to_search[x][ri] != 0);
 
         if CHECK_SPIN {
-            let mut m = (to_search[x][ri] >> 1) & !cm.get(x, r);
-            while (m & to_search[x][ri]) != m {
-                to_search[x][ri] |= m;
-                m |= (m >> 1) & !cm.get(x, r);
-            }
+            let m = fill_down(to_search[x][ri], !cm.get(x, r));
+            to_search[x][ri] |= m;
             spin_set[x][ri][SpinType::NoSpin as usize] |= m;
         } else {
-            let mut m = (to_search[x][ri] >> 1) & !to_search[x][ri] & !searched[x][ri];
-            while m != 0 {
-                to_search[x][ri] |= m;
-                m = (m >> 1) & !searched[x][ri];
-            }
+            to_search[x][ri] |= fill_down(to_search[x][ri], !searched[x][ri]);
         }
 
         if CHECK_SPIN {
@@ -202,11 +290,7 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
                     let r1 = rotate(d, r);
                     let rc = canonical_r(p, r1);
                     let off = canonical_offset(p, r) - canonical_offset(p, r1);
-                    let n = if !ACTIVE_RULES.srs_plus && kicks.len() == 6 {
-                        2
-                    } else {
-                        kicks.len()
-                    };
+                    let n = config.kicks.kick_count();
 
                     let mut current = to_search[x][ri];
 
@@ -265,7 +349,7 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
                     }
                 };
 
-            let ki = kick_index(p, ACTIVE_RULES.srs_plus);
+            let ki = config.kicks.kick_index(p);
             do_rotate(
                 &KICKS[ki][Direction::Cw as usize],
                 Direction::Cw,
@@ -287,10 +371,11 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
                 spin_map,
             );
 
-            if ACTIVE_RULES.enable_180 {
+            if config.allow_180() {
                 let ki180 = kick_180_index(p);
                 do_rotate_180::<P, CHECK_SPIN>(&mut RotateContext {
                     kicks_rot: &KICKS_180[ki180],
+                    kick_count: config.kicks.kick_180_count(),
                     current_search: to_search[x][ri],
                     x,
                     r,
@@ -364,6 +449,7 @@ fn generate_inner<const P: usize, const CHECK_SPIN: bool>(
 
 struct RotateContext<'a> {
     kicks_rot: &'a [[Coordinates; 6]; ROTATION_NB],
+    kick_count: usize,
     current_search: Bitboard,
     x: usize,
     r: Rotation,
@@ -382,11 +468,7 @@ fn do_rotate_180<const P: usize, const CHECK_SPIN: bool>(ctx: &mut RotateContext
     let rc = canonical_r(p, r1);
     let off = canonical_offset(p, ctx.r) - canonical_offset(p, r1);
     let kicks = &ctx.kicks_rot[ri];
-    let n = if !ACTIVE_RULES.srs_plus && kicks.len() == 6 {
-        2
-    } else {
-        kicks.len()
-    };
+    let n = ctx.kick_count;
 
     let remaining_index =
         |x: i32, r: Rotation| -> Bitboard { bb(x * ROTATION_NB as i32 + r as i32) };
@@ -448,7 +530,11 @@ fn do_rotate_180<const P: usize, const CHECK_SPIN: bool>(ctx: &mut RotateContext
     }
 }
 
-fn generate16<const P: usize>(cols: &[Bitboard; COL_NB], moves: &mut MoveBuffer) {
+fn generate16<const P: usize>(
+    cols: &[Bitboard; COL_NB],
+    moves: &mut MoveBuffer,
+    config: &MoveGenConfig,
+) {
     let p = piece_from_index(P);
     // all const — compiler resolves at monomorphization
     let canonical_sz = canonical_size(p);
@@ -520,13 +606,7 @@ fn generate16<const P: usize>(cols: &[Bitboard; COL_NB], moves: &mut MoveBuffer)
         to_search[x] = 0;
 
         // softdrops
-        {
-            let mut m = (current >> 1) & !searched[x] & s_mask;
-            while m != 0 {
-                current |= m;
-                m = (m >> 1) & s_mask & !searched[x];
-            }
-        }
+        current |= fill_down(current, !searched[x] & s_mask);
 
         // harddrops
         {
@@ -594,11 +674,7 @@ fn generate16<const P: usize>(cols: &[Bitboard; COL_NB], moves: &mut MoveBuffer)
                     let r1 = rotate(d, r);
                     let shift_dest = (r1 as usize) * 16;
                     let off = canonical_offset(p, r) - canonical_offset(p, r1);
-                    let n = if !ACTIVE_RULES.srs_plus && kicks.len() == 6 {
-                        2
-                    } else {
-                        kicks.len()
-                    };
+                    let n = config.kicks.kick_count();
 
                     let mut src = src_bits;
                     for kick in kicks.iter().take(n) {
@@ -632,7 +708,7 @@ fn generate16<const P: usize>(cols: &[Bitboard; COL_NB], moves: &mut MoveBuffer)
                 }
             };
 
-            let ki = kick_index(p, ACTIVE_RULES.srs_plus);
+            let ki = config.kicks.kick_index(p);
             do_process(
                 &KICKS[ki][Direction::Cw as usize],
                 Direction::Cw,
@@ -654,10 +730,11 @@ fn generate16<const P: usize>(cols: &[Bitboard; COL_NB], moves: &mut MoveBuffer)
                 x,
             );
 
-            if ACTIVE_RULES.enable_180 {
+            if config.allow_180() {
                 let ki180 = kick_180_index(p);
                 do_process_180::<P>(&mut ProcessContext {
                     kicks_rot: &KICKS_180[ki180],
+                    kick_count: config.kicks.kick_180_count(),
                     d: Direction::Flip,
                     current: &mut current,
                     to_search: &mut to_search,
@@ -675,6 +752,7 @@ fn generate16<const P: usize>(cols: &[Bitboard; COL_NB], moves: &mut MoveBuffer)
 
 struct ProcessContext<'a> {
     kicks_rot: &'a [[Coordinates; 6]; ROTATION_NB],
+    kick_count: usize,
     d: Direction,
     current: &'a mut Bitboard,
     to_search: &'a mut [Bitboard; COL_NB],
@@ -697,11 +775,7 @@ fn do_process_180<const P: usize>(ctx: &mut ProcessContext<'_>) {
         let r1 = rotate(ctx.d, r);
         let shift_dest = (r1 as usize) * 16;
         let off = canonical_offset(p, r) - canonical_offset(p, r1);
-        let n = if !ACTIVE_RULES.srs_plus && kicks.len() == 6 {
-            2
-        } else {
-            kicks.len()
-        };
+        let n = ctx.kick_count;
 
         let mut src = src_bits;
         for kick in kicks.iter().take(n) {
@@ -737,6 +811,294 @@ fn do_process_180<const P: usize>(ctx: &mut ProcessContext<'_>) {
 
 // -- generate: 1:1 port of generate() dispatch --
 pub fn generate(b: &Board, moves: &mut MoveBuffer, p: Piece, force: bool) {
+    generate_with(b, moves, p, force, &MoveGenConfig::default());
+}
+
+/// `generate` with explicit options (kick system, ...).
+pub fn generate_with(
+    b: &Board,
+    moves: &mut MoveBuffer,
+    p: Piece,
+    force: bool,
+    config: &MoveGenConfig,
+) {
+    generate_with_map(b, moves, p, force, config, &mut None);
+}
+
+/// `generate(board, .., piece, true)` reading a prebuilt `collision` map
+/// instead of building one. A map is piece-specific (the minos differ), so it
+/// must come from `CollisionMap::from_board(board, piece)`; to reuse maps for
+/// several pieces on one board, use `BoardCollisionCache`.
+///
+/// Only the full-height generator reads the map: T with T-spins on, pieces
+/// eligible for all-spin checks, and any piece on a board taller than 13
+/// rows. A low board otherwise takes the packed 16-bit
+/// generator, which builds its own map, so under the default rules an O on
+/// a low board never touches `collision`.
+pub fn generate_moves_from_map(collision: &CollisionMap, piece: Piece, board: &Board) -> Vec<Move> {
+    debug_assert_eq!(collision.piece(), piece, "collision map built for another piece");
+    let mut moves = MoveBuffer::new();
+    // the map is a small array of bitboards; copying it into the slot keeps
+    // the generator's lazily-filled slot interface
+    let mut slot = Some(*collision);
+    generate_with_map(board, &mut moves, piece, true, &MoveGenConfig::default(), &mut slot);
+    moves.as_slice().to_vec()
+}
+
+/// Memoized per-piece collision maps for one board, for querying several
+/// pieces (or the same piece again, e.g. hold alternatives) without
+/// rebuilding them. A map only fits the piece it was built for, so there is
+/// one slot per piece, filled the first time a generation needs it. Slots
+/// stay empty for generations that take the low-board 16-bit path (see
+/// `generate_moves_from_map`).
+pub struct BoardCollisionCache {
+    board: Board,
+    maps: [Option<CollisionMap>; PIECE_NB],
+}
+
+impl BoardCollisionCache {
+    pub fn new(board: &Board) -> Self {
+        Self {
+            board: board.clone(),
+            maps: std::array::from_fn(|_| None),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Same moves as `generate` on the cached board.
+    pub fn generate(&mut self, moves: &mut MoveBuffer, p: Piece, force: bool) {
+        let config = MoveGenConfig::default();
+        let slot = &mut self.maps[p as usize];
+        generate_with_map(&self.board, moves, p, force, &config, slot);
+    }
+}
+
+// `generate_with` reading the collision map for `p` from `map`, building it
+// there if empty. The map must have been built from `b` for `p`.
+fn generate_with_map(
+    b: &Board,
+    moves: &mut MoveBuffer,
+    p: Piece,
+    force: bool,
+    config: &MoveGenConfig,
+    map: &mut Option<CollisionMap>,
+) {
+    let start = moves.len();
+    generate_unfiltered(b, moves, p, force, config, map);
+    if let Some(limit) = config.max_soft_drop {
+        let cols = b.compute_cols();
+        moves.retain_from(start, |m| tuck_depth(&cols, m) <= limit as i32);
+    }
+    if config.ceiling < BOARD_HEIGHT {
+        moves.retain_from(start, |m| top_mino_y(m) < config.ceiling as i32);
+    }
+}
+
+pub(crate) fn footprint(m: &Move) -> [(i32, i32); 4] {
+    let pc = m.cells();
+    let mut cells = [(m.x(), m.y()); 4];
+    for (cell, c) in cells[1..].iter_mut().zip(pc.coords.iter()) {
+        *cell = (m.x() + c.x as i32, m.y() + c.y as i32);
+    }
+    cells.sort_unstable();
+    cells
+}
+
+/// Replays `moves` from `board`, marking each legal if it rests on the stack
+/// without overlap and its footprint is reachable per `generate`. Illegal
+/// moves are skipped (not applied), so later moves see the last good board.
+pub fn validate_moves(board: &Board, moves: &[Move]) -> Vec<bool> {
+    let mut b = board.clone();
+    moves
+        .iter()
+        .map(|m| {
+            if !b.legal_lock_placement(m) {
+                return false;
+            }
+            let mut buf = MoveBuffer::new();
+            generate(&b, &mut buf, m.piece(), true);
+            let target = footprint(m);
+            let ok = buf.iter().any(|g| footprint(g) == target);
+            if ok {
+                b.do_move(m);
+            }
+            ok
+        })
+        .collect()
+}
+
+/// Whether a vertical I can drop to the bottom of the well in `well_column`
+/// (the lowest empty cell above the column's solid base), i.e. the shaft
+/// isn't blocked by an overhang. Only non-spin placements count: an I kicked
+/// in under a roof isn't something well maintenance can rely on.
+pub fn can_fill_well(board: &Board, well_column: usize) -> bool {
+    debug_assert!(well_column < COL_NB);
+    let bottom = (!board.cols[well_column]).trailing_zeros() as i32;
+    let mut moves = MoveBuffer::new();
+    generate(board, &mut moves, Piece::I, true);
+    moves.iter().any(|m| {
+        let cells = footprint(m);
+        m.spin() == SpinType::NoSpin
+            && cells.iter().all(|&(x, _)| x == well_column as i32)
+            && cells[0].1 == bottom
+    })
+}
+
+/// Whether some reachable placement of `piece` clears at least one line.
+/// Stops at the first one found and never applies a move.
+pub fn has_clearing_placement(board: &Board, piece: Piece) -> bool {
+    let mut moves = MoveBuffer::new();
+    generate(board, &mut moves, piece, true);
+    moves.iter().any(|m| {
+        let cells = footprint(m);
+        cells.iter().any(|&(_, y)| {
+            cells
+                .iter()
+                .filter(|c| c.1 == y)
+                .fold(board.rows[y as usize], |row, c| row | 1 << c.0)
+                == FULL_ROW
+        })
+    })
+}
+
+fn top_mino_y(m: &Move) -> i32 {
+    let top = m.cells().coords.iter().map(|c| c.y as i32).max().unwrap_or(0).max(0);
+    m.y() + top
+}
+
+/// Straight hard drop from the spawn row at `rotation`/`x`. None if the
+/// piece is out of bounds or blocked at the spawn row.
+///
+/// Always `SpinType::NoSpin`; no spin classification is run on the landing
+/// spot. A spin needs a rotation as the last move, and a straight drop has
+/// none, even where the corner test would pass (e.g. a T East against the
+/// wall onto a step). Spin placements come from `generate` (or
+/// `generate_spin_placements`), which tracks the rotation that got there.
+pub fn hard_drop(b: &Board, piece: Piece, rotation: Rotation, x: i32) -> Option<Move> {
+    let mut m = Move::new(piece, rotation, x, ACTIVE_RULES.spawn_row, false);
+    if b.obstructed_move(&m) {
+        return None;
+    }
+    loop {
+        let below = Move::new(piece, rotation, x, m.y() - 1, false);
+        if b.obstructed_move(&below) {
+            return Some(m);
+        }
+        m = below;
+    }
+}
+
+/// Every spin placement (mini or full, any piece) on `b`.
+pub fn generate_spin_placements(b: &Board, piece: Piece) -> Vec<Move> {
+    let mut moves = MoveBuffer::new();
+    generate(b, &mut moves, piece, true);
+    moves
+        .iter()
+        .copied()
+        .filter(|m| m.spin() != SpinType::NoSpin)
+        .collect()
+}
+
+pub fn spin_count(b: &Board, piece: Piece) -> usize {
+    generate_spin_placements(b, piece).len()
+}
+
+/// Distinct boards reachable by locking `piece` (after line clears), keyed by
+/// `content_hash`; different placements can collapse to the same board.
+pub fn distinct_outcomes(b: &Board, piece: Piece) -> usize {
+    let mut moves = MoveBuffer::new();
+    generate(b, &mut moves, piece, true);
+    let mut seen = std::collections::HashSet::with_capacity(moves.len());
+    for m in moves.iter() {
+        let mut after = b.clone();
+        after.do_move(m);
+        seen.insert(after.content_hash());
+    }
+    seen.len()
+}
+
+/// Reachable lock positions of `piece` as bitboards, `masks[x][rotation]`
+/// bit y = pivot at (x, y): the generator's `move_set`, spins ignored. Only
+/// canonical rotations are set (North/East for I, S and Z, North for O), so
+/// each bit is one `generate` placement. For drawing every landing spot
+/// without building moves.
+///
+/// The masks are indexed by column and rotation rather than flattened, and
+/// always come from the full-height generator without spin checks.
+/// `generate` may instead take the packed low-board path or the
+/// spin-checking one; those reach the same lock positions and differ only
+/// in the spin each placement reports, so the bits still match `generate`
+/// one for one.
+pub fn locking_masks(board: &Board, piece: Piece) -> [[Bitboard; ROTATION_NB]; COL_NB] {
+    let cols = board.compute_cols();
+    let cm = CollisionMap::new(&cols, piece);
+    let h = bitlen(cols.iter().fold(0, |m, col| m | col));
+    let slow = h as i32 > ACTIVE_RULES.spawn_row - 3;
+    let config = MoveGenConfig::default();
+    let mut moves = MoveBuffer::new();
+    let mut masks = [[0; ROTATION_NB]; COL_NB];
+    let (m, c) = (&mut moves, &config);
+    match piece {
+        Piece::I => generate_inner_into::<{ Piece::I as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+        Piece::O => generate_inner_into::<{ Piece::O as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+        Piece::T => generate_inner_into::<{ Piece::T as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+        Piece::L => generate_inner_into::<{ Piece::L as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+        Piece::J => generate_inner_into::<{ Piece::J as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+        Piece::S => generate_inner_into::<{ Piece::S as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+        Piece::Z => generate_inner_into::<{ Piece::Z as usize }, false>(
+            &cm, m, slow, true, None, c, &mut masks,
+        ),
+    }
+    masks
+}
+
+/// `generate`, keeping one placement of each left-right mirror pair. Only
+/// meaningful on a mirror-symmetric board, where both halves of a pair lead
+/// to mirrored positions; on any other board every placement is returned.
+pub fn generate_moves_dedup_symmetry(board: &Board, piece: Piece) -> Vec<Move> {
+    let mut moves = MoveBuffer::new();
+    generate(board, &mut moves, piece, true);
+    if !board.same_shape(&board.mirror_x()) {
+        return moves.as_slice().to_vec();
+    }
+
+    let mut seen = std::collections::HashSet::with_capacity(moves.len());
+    moves
+        .iter()
+        .copied()
+        .filter(|m| {
+            let cells = footprint(m);
+            let mut mirrored = cells.map(|(x, y)| (COL_NB as i32 - 1 - x, y));
+            mirrored.sort_unstable();
+            let seen_mirror = seen.contains(&(mirrored, m.spin()));
+            seen.insert((cells, m.spin())) && !seen_mirror
+        })
+        .collect()
+}
+
+fn generate_unfiltered(
+    b: &Board,
+    moves: &mut MoveBuffer,
+    p: Piece,
+    force: bool,
+    config: &MoveGenConfig,
+    map: &mut Option<CollisionMap>,
+) {
     debug_assert!(ACTIVE_RULES.spawn_row > 0);
 
     // precompute columns once — avoids repeated 40-row iteration in col()
@@ -753,23 +1115,24 @@ pub fn generate(b: &Board, moves: &mut MoveBuffer, p: Piece, force: bool) {
     let slow = h as i32 > ACTIVE_RULES.spawn_row - 3;
     let low = !slow && h <= 13;
 
-    let allspin_eligible = p != Piece::T && p != Piece::O && ACTIVE_RULES.enable_allspin;
+    let allspin_eligible =
+        p != Piece::T && p != Piece::O && config.spin_rule != SpinRule::TSpinOnly;
     if low && (p != Piece::T || !ACTIVE_RULES.enable_tspin) && !allspin_eligible {
         match p {
-            Piece::I => generate16::<{ Piece::I as usize }>(&cols, moves),
-            Piece::O => generate16::<{ Piece::O as usize }>(&cols, moves),
-            Piece::T => generate16::<{ Piece::T as usize }>(&cols, moves),
-            Piece::L => generate16::<{ Piece::L as usize }>(&cols, moves),
-            Piece::J => generate16::<{ Piece::J as usize }>(&cols, moves),
-            Piece::S => generate16::<{ Piece::S as usize }>(&cols, moves),
-            Piece::Z => generate16::<{ Piece::Z as usize }>(&cols, moves),
+            Piece::I => generate16::<{ Piece::I as usize }>(&cols, moves, config),
+            Piece::O => generate16::<{ Piece::O as usize }>(&cols, moves, config),
+            Piece::T => generate16::<{ Piece::T as usize }>(&cols, moves, config),
+            Piece::L => generate16::<{ Piece::L as usize }>(&cols, moves, config),
+            Piece::J => generate16::<{ Piece::J as usize }>(&cols, moves, config),
+            Piece::S => generate16::<{ Piece::S as usize }>(&cols, moves, config),
+            Piece::Z => generate16::<{ Piece::Z as usize }>(&cols, moves, config),
         }
         return;
     }
 
     match p {
         Piece::T if ACTIVE_RULES.enable_tspin => {
-            let cm = CollisionMap::new(&cols, Piece::T);
+            let cm = &*map.get_or_insert_with(|| CollisionMap::new(&cols, Piece::T));
             let mut check_spin = false;
             let mut spin_map = [[0u64; 5]; COL_NB]; // [col][0=3corner, 1+r=face_corner]
 
@@ -807,72 +1170,75 @@ pub fn generate(b: &Board, moves: &mut MoveBuffer, p: Piece, force: bool) {
 
             if check_spin {
                 generate_inner::<{ Piece::T as usize }, true>(
-                    &cm,
+                    cm,
                     moves,
                     slow,
                     force,
                     Some(&spin_map),
+                    config,
                 );
             } else if low {
                 match p {
-                    Piece::I => generate16::<{ Piece::I as usize }>(&cols, moves),
-                    Piece::O => generate16::<{ Piece::O as usize }>(&cols, moves),
-                    Piece::T => generate16::<{ Piece::T as usize }>(&cols, moves),
-                    Piece::L => generate16::<{ Piece::L as usize }>(&cols, moves),
-                    Piece::J => generate16::<{ Piece::J as usize }>(&cols, moves),
-                    Piece::S => generate16::<{ Piece::S as usize }>(&cols, moves),
-                    Piece::Z => generate16::<{ Piece::Z as usize }>(&cols, moves),
+                    Piece::I => generate16::<{ Piece::I as usize }>(&cols, moves, config),
+                    Piece::O => generate16::<{ Piece::O as usize }>(&cols, moves, config),
+                    Piece::T => generate16::<{ Piece::T as usize }>(&cols, moves, config),
+                    Piece::L => generate16::<{ Piece::L as usize }>(&cols, moves, config),
+                    Piece::J => generate16::<{ Piece::J as usize }>(&cols, moves, config),
+                    Piece::S => generate16::<{ Piece::S as usize }>(&cols, moves, config),
+                    Piece::Z => generate16::<{ Piece::Z as usize }>(&cols, moves, config),
                 }
             } else {
-                generate_inner::<{ Piece::T as usize }, false>(&cm, moves, slow, force, None);
+                generate_inner::<{ Piece::T as usize }, false>(
+                    cm, moves, slow, force, None, config,
+                );
             }
         }
         _ => {
-            let cm = CollisionMap::new(&cols, p);
+            let cm = &*map.get_or_insert_with(|| CollisionMap::new(&cols, p));
             if allspin_eligible {
                 match p {
-                    Piece::I => {
-                        generate_inner::<{ Piece::I as usize }, true>(&cm, moves, slow, force, None)
-                    }
-                    Piece::L => {
-                        generate_inner::<{ Piece::L as usize }, true>(&cm, moves, slow, force, None)
-                    }
-                    Piece::J => {
-                        generate_inner::<{ Piece::J as usize }, true>(&cm, moves, slow, force, None)
-                    }
-                    Piece::S => {
-                        generate_inner::<{ Piece::S as usize }, true>(&cm, moves, slow, force, None)
-                    }
-                    Piece::Z => {
-                        generate_inner::<{ Piece::Z as usize }, true>(&cm, moves, slow, force, None)
-                    }
-                    _ => {
-                        generate_inner::<{ Piece::T as usize }, false>(&cm, moves, slow, force, None)
-                    }
+                    Piece::I => generate_inner::<{ Piece::I as usize }, true>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::L => generate_inner::<{ Piece::L as usize }, true>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::J => generate_inner::<{ Piece::J as usize }, true>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::S => generate_inner::<{ Piece::S as usize }, true>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::Z => generate_inner::<{ Piece::Z as usize }, true>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    _ => generate_inner::<{ Piece::T as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
                 }
             } else {
                 match p {
-                    Piece::I => {
-                        generate_inner::<{ Piece::I as usize }, false>(&cm, moves, slow, force, None)
-                    }
-                    Piece::O => {
-                        generate_inner::<{ Piece::O as usize }, false>(&cm, moves, slow, force, None)
-                    }
-                    Piece::L => {
-                        generate_inner::<{ Piece::L as usize }, false>(&cm, moves, slow, force, None)
-                    }
-                    Piece::J => {
-                        generate_inner::<{ Piece::J as usize }, false>(&cm, moves, slow, force, None)
-                    }
-                    Piece::S => {
-                        generate_inner::<{ Piece::S as usize }, false>(&cm, moves, slow, force, None)
-                    }
-                    Piece::Z => {
-                        generate_inner::<{ Piece::Z as usize }, false>(&cm, moves, slow, force, None)
-                    }
-                    Piece::T => {
-                        generate_inner::<{ Piece::T as usize }, false>(&cm, moves, slow, force, None)
-                    }
+                    Piece::I => generate_inner::<{ Piece::I as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::O => generate_inner::<{ Piece::O as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::L => generate_inner::<{ Piece::L as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::J => generate_inner::<{ Piece::J as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::S => generate_inner::<{ Piece::S as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::Z => generate_inner::<{ Piece::Z as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
+                    Piece::T => generate_inner::<{ Piece::T as usize }, false>(
+                        cm, moves, slow, force, None, config,
+                    ),
                 }
             }
         }
@@ -882,6 +1248,7 @@ pub fn generate(b: &Board, moves: &mut MoveBuffer, p: Piece, force: bool) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::FULL_ROW;
 
     #[test]
     fn test_generate_i_piece_empty_board() {
@@ -917,6 +1284,176 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_spin_rule_t_spin_only_drops_s_spin() {
+        // S North at (4,0) tucks into the notch and can't shift or lift
+        let b = board_from_rows(&[FULL_ROW & !(0b11 << 3), FULL_ROW & !(0b11 << 4)]);
+        let spin_of = |spin_rule: SpinRule| {
+            let config = MoveGenConfig {
+                spin_rule,
+                ..MoveGenConfig::default()
+            };
+            let mut moves = MoveBuffer::new();
+            generate_with(&b, &mut moves, Piece::S, true, &config);
+            moves
+                .iter()
+                .find(|m| m.rotation() == Rotation::North && m.x() == 4 && m.y() == 0)
+                .map(|m| m.spin())
+                .unwrap_or_else(|| panic!("S tuck not generated"))
+        };
+        assert_eq!(spin_of(SpinRule::AllMini), SpinType::Mini);
+        assert_eq!(spin_of(SpinRule::AllSpinPlus), SpinType::Mini);
+        assert_eq!(spin_of(SpinRule::TSpinOnly), SpinType::NoSpin);
+    }
+
+    #[test]
+    fn test_locking_masks_match_move_count() {
+        // low: `generate` takes the 16-bit path for O and spin checks for
+        // the rest; tall: the full-height path, searching from spawn
+        let low = board_from_rows(&[0b0111111011, 0b0011100001]);
+        let tall = board_from_rows(&[0b1000000011; 19]);
+        // T-spin double slot under an overhang at x=3
+        let tsd = board_from_rows(&[FULL_ROW & !(1 << 4), FULL_ROW & !(0b111 << 3), 0b1111]);
+        assert!(generate_spin_placements(&tsd, Piece::T).iter().any(|m| m.spin() == SpinType::Full));
+        for (b, p) in [&low, &tall, &tsd].into_iter().flat_map(|b| ALL_PIECES.map(|p| (b, p))) {
+            let mut moves = MoveBuffer::new();
+            generate(b, &mut moves, p, true);
+            let masks = locking_masks(b, p);
+
+            let bits: u32 = masks.iter().flatten().map(|m| m.count_ones()).sum();
+            assert_eq!(bits as usize, moves.len(), "{p:?}");
+            for m in moves.iter() {
+                let mask = masks[m.x() as usize][m.rotation() as usize];
+                assert!(mask >> m.y() & 1 != 0, "{m:?} missing");
+            }
+        }
+
+        let empty = locking_masks(&Board::new(), Piece::I);
+        let bits: u32 = empty.iter().flatten().map(|m| m.count_ones()).sum();
+        assert_eq!(bits, 17);
+    }
+
+    #[test]
+    fn test_symmetry_dedup_halves_t_on_empty_board() {
+        let board = Board::new();
+        let deduped = generate_moves_dedup_symmetry(&board, Piece::T);
+        assert_eq!(deduped.len(), 17);
+
+        // a lopsided board is left alone
+        let lopsided = board_from_rows(&[0b1]);
+        let mut all = MoveBuffer::new();
+        generate(&lopsided, &mut all, Piece::T, true);
+        assert_eq!(generate_moves_dedup_symmetry(&lopsided, Piece::T).len(), all.len());
+    }
+
+    // the per-row loop fill_down replaced; returns the fill and its pass count
+    fn fill_down_iterative(from: Bitboard, free: Bitboard) -> (Bitboard, u32) {
+        let mut filled = 0;
+        let mut m = (from >> 1) & free;
+        let mut passes = 0;
+        while m & !filled != 0 {
+            filled |= m;
+            m = (m >> 1) & free;
+            passes += 1;
+        }
+        (filled, passes)
+    }
+
+    #[test]
+    fn test_fill_down_matches_iterative() {
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..10_000 {
+            let (from, free) = (next() & next(), next() | next());
+            assert_eq!(fill_down(from, free), fill_down_iterative(from, free).0);
+        }
+        // per-segment masks stop the fill at rotation boundaries
+        let s_mask = 0x7FFF_7FFF_7FFF_7FFFu64;
+        assert_eq!(fill_down(1 << 16, s_mask), 0);
+        assert_eq!(fill_down(1 << 20, s_mask), 0xF << 16);
+
+        // a spawn-height piece over an empty 40-row column: 39 passes
+        // of the loop against a fixed six steps
+        let tall = (1u64 << BOARD_HEIGHT) - 1;
+        let (fill, passes) = fill_down_iterative(1 << 39, tall);
+        assert_eq!(fill_down(1 << 39, tall), fill);
+        assert_eq!(passes, 39);
+    }
+
+    // `Board::from` for the bottom rows only
+    fn board_from_rows(rows: &[u16]) -> Board {
+        let mut all = [0u16; BOARD_HEIGHT];
+        all[..rows.len()].copy_from_slice(rows);
+        Board::from(&all)
+    }
+
+    fn count_with(b: &Board, p: Piece, kicks: KickSystem) -> usize {
+        let mut moves = MoveBuffer::new();
+        generate_with(
+            b,
+            &mut moves,
+            p,
+            false,
+            &MoveGenConfig {
+                kicks,
+                ..MoveGenConfig::default()
+            },
+        );
+        moves.len()
+    }
+
+    #[test]
+    fn test_kick_system_empty_board_counts_match() {
+        // every empty-board placement is a plain drop, so kicks never matter
+        let b = Board::new();
+        for &p in &ALL_PIECES {
+            let plus = count_with(&b, p, KickSystem::SrsPlus);
+            assert_eq!(plus, count_with(&b, p, KickSystem::Srs), "{:?}", p);
+            assert_eq!(plus, count_with(&b, p, KickSystem::None), "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_kick_system_overhang_counts_differ() {
+        // T placements only reachable through a kicked 180; SRS keeps just
+        // the in-place one
+        let b = board_from_rows(&[0b0111000011, 0b1111100000, 0b1011111100]);
+        assert!(count_with(&b, Piece::T, KickSystem::SrsPlus) > count_with(&b, Piece::T, KickSystem::Srs));
+        let b = board_from_rows(&[0b1111111001, 0b1111110001, 0b1111110111]);
+        // L tuck needs a real (non-zero) kick offset
+        assert!(count_with(&b, Piece::L, KickSystem::Srs) > count_with(&b, Piece::L, KickSystem::None));
+    }
+
+    #[test]
+    fn test_srs_rules_keep_in_place_180() {
+        let srs = MoveGenConfig::for_rules(&Rules {
+            srs_plus: false,
+            ..ACTIVE_RULES
+        });
+        assert_eq!(srs.kicks, KickSystem::Srs);
+        assert_eq!(srs.allow_180(), ACTIVE_RULES.enable_180);
+        assert!(!MoveGenConfig {
+            kicks: KickSystem::None,
+            ..srs
+        }
+        .allow_180());
+    }
+
+    #[test]
+    fn test_default_config_matches_generate() {
+        let b = board_from_rows(&[0b1111111001, 0b1111110001, 0b1111110111]);
+        for &p in &ALL_PIECES {
+            let mut moves = MoveBuffer::new();
+            generate(&b, &mut moves, p, false);
+            assert_eq!(moves.len(), count_with(&b, p, KickSystem::SrsPlus));
+        }
+    }
+
     #[test]
     fn test_movelist_no_duplicates() {
         let b = Board::new();
@@ -933,4 +1470,246 @@ mod tests {
             assert!(ml.size() > 0, "No moves for {:?}", p);
         }
     }
+
+    #[test]
+    fn test_max_soft_drop_excludes_deep_tucks() {
+        // 3x3 cave at x=0..2 under a roof, entered through the x=2 shaft
+        let b = board_from_rows(&[0x3F8, 0x3F8, 0x3F8, 0x3FB]);
+        let cols = b.compute_cols();
+
+        let mut free = MoveBuffer::new();
+        generate(&b, &mut free, Piece::L, false);
+        assert!(free.iter().any(|m| tuck_depth(&cols, m) > 1));
+
+        let mut limited = MoveBuffer::new();
+        let config = MoveGenConfig {
+            max_soft_drop: Some(1),
+            ..MoveGenConfig::default()
+        };
+        generate_with(&b, &mut limited, Piece::L, false, &config);
+        assert!(limited.len() < free.len());
+        assert!(limited.iter().all(|m| tuck_depth(&cols, m) <= 1));
+        // plain drops are unaffected
+        let plain = free.iter().filter(|m| tuck_depth(&cols, m) == 0).count();
+        assert_eq!(limited.iter().filter(|m| tuck_depth(&cols, m) == 0).count(), plain);
+    }
+
+    #[test]
+    fn test_spin_placements_s_notch() {
+        // S-shaped notch under x=3: only reachable by rotating in
+        let b = board_from_rows(&[0x3FF & !(0b11 << 3), 0x3FF & !(0b11 << 4)]);
+        let spins = generate_spin_placements(&b, Piece::S);
+        assert_eq!(spins.len(), 1);
+        let m = spins[0];
+        assert_eq!((m.piece(), m.rotation(), m.x(), m.y()), (Piece::S, Rotation::North, 4, 0));
+        assert_eq!(spin_count(&b, Piece::S), spins.len());
+    }
+
+    #[test]
+    fn test_spin_placements_empty_board() {
+        let b = Board::new();
+        for p in ALL_PIECES {
+            assert_eq!(spin_count(&b, p), 0, "{p:?}");
+        }
+    }
+
+    #[test]
+    fn test_hard_drop_flat_i() {
+        let b = board_from_rows(&[0x3FF & !1, 0x3FF & !1]);
+        let m = hard_drop(&b, Piece::I, Rotation::North, 4)
+            .unwrap_or_else(|| panic!("flat I fits"));
+        assert_eq!((m.x(), m.y(), m.spin()), (4, 2, SpinType::NoSpin));
+        assert!(b.legal_lock_placement(&m));
+
+        let mut moves = MoveBuffer::new();
+        generate(&b, &mut moves, Piece::I, false);
+        assert!(moves.iter().any(|g| g.raw() == m.raw()));
+
+        // off the board / blocked at spawn
+        assert!(hard_drop(&b, Piece::I, Rotation::North, 9).is_none());
+        let mut full = Board::new();
+        full.spawn_garbage(23, 0);
+        assert!(hard_drop(&full, Piece::T, Rotation::North, 4).is_none());
+    }
+
+    #[test]
+    fn test_hard_drop_t_over_slot_is_not_a_spin() {
+        // TSD slot under an overhang at x=3: a straight drop lands on the
+        // overhang, and only rotating in reaches the slot with a spin
+        let b = board_from_rows(&[0x3FF & !(1 << 4), 0x3FF & !(0b111 << 3), 1 << 3]);
+        let m = hard_drop(&b, Piece::T, Rotation::South, 4)
+            .unwrap_or_else(|| panic!("T fits above the slot"));
+        assert_eq!(m.spin(), SpinType::NoSpin);
+        assert!(m.y() > 1);
+
+        let mut moves = MoveBuffer::new();
+        generate(&b, &mut moves, Piece::T, false);
+        assert!(moves
+            .iter()
+            .any(|g| g.y() == 1 && g.x() == 4 && g.spin() == SpinType::Full));
+    }
+
+    #[test]
+    fn test_distinct_outcomes_collapses_equivalent_clears() {
+        // two rows open at x=0..2: T North clears row 0 and T South clears
+        // row 1, and both leave the same single row with x=1 filled
+        let b = board_from_rows(&[0x3F8, 0x3F8]);
+        let mut moves = MoveBuffer::new();
+        generate(&b, &mut moves, Piece::T, true);
+
+        let north = Move::new(Piece::T, Rotation::North, 1, 0, false);
+        let south = Move::new(Piece::T, Rotation::South, 1, 1, false);
+        let outcome = |m: &Move| {
+            let mut after = b.clone();
+            (after.do_move(m), after.rows[0], after.rows[1])
+        };
+        assert_eq!(outcome(&north), (1, 0x3FA, 0));
+        assert_eq!(outcome(&north), outcome(&south));
+
+        assert_eq!(distinct_outcomes(&b, Piece::T), moves.len() - 1);
+        let empty = Board::new();
+        let mut flat = MoveBuffer::new();
+        generate(&empty, &mut flat, Piece::T, true);
+        assert_eq!(distinct_outcomes(&empty, Piece::T), flat.len());
+    }
+
+    #[test]
+    fn test_ceiling_excludes_high_placements() {
+        // 16-high stack with an open well at x=0
+        let b = board_from_rows(&[0x3FE; 16]);
+        let mut free = MoveBuffer::new();
+        generate(&b, &mut free, Piece::J, false);
+        assert!(free.iter().any(|m| top_mino_y(m) >= 18));
+
+        let config = MoveGenConfig {
+            ceiling: 18,
+            ..MoveGenConfig::default()
+        };
+        let mut capped = MoveBuffer::new();
+        generate_with(&b, &mut capped, Piece::J, false, &config);
+        assert!(capped.len() < free.len());
+        assert!(capped.iter().all(|m| top_mino_y(m) < 18));
+        let low = free.iter().filter(|m| top_mino_y(m) < 18).count();
+        assert_eq!(capped.len(), low);
+    }
+
+    #[test]
+    fn test_validate_moves_flags_bad_replay_entries() {
+        let b = Board::new();
+        let flat_i = Move::new(Piece::I, Rotation::North, 4, 0, false);
+        let floating_o = Move::new(Piece::O, Rotation::North, 0, 5, false);
+        let on_i = Move::new(Piece::O, Rotation::North, 4, 1, false);
+        let overlapping_t = Move::new(Piece::T, Rotation::North, 4, 1, false);
+        let floor_t = Move::new(Piece::T, Rotation::North, 1, 0, false);
+
+        let result = validate_moves(&b, &[flat_i, floating_o, on_i, overlapping_t, floor_t]);
+        assert_eq!(result, vec![true, false, true, false, true]);
+
+        // the O on top of the I is only legal because the I was applied
+        assert_eq!(validate_moves(&b, &[on_i]), vec![false]);
+
+        // resting but sealed off: an O-sized cave under a roof
+        let cave = 0x3FF & !(0b11 << 4);
+        let sealed = board_from_rows(&[cave, cave, 0x3FE]);
+        let cave_o = Move::new(Piece::O, Rotation::North, 4, 0, false);
+        assert!(sealed.legal_lock_placement(&cave_o));
+        assert_eq!(validate_moves(&sealed, &[cave_o]), vec![false]);
+    }
+
+    #[test]
+    fn test_can_fill_well_open_vs_capped() {
+        let well = 0x3FF & !(1 << 9);
+        let open = board_from_rows(&[well, well, well, well]);
+        assert!(can_fill_well(&open, 9));
+        // x=0 has no well: bottom is row 4 on top of the stack
+        assert!(can_fill_well(&open, 0));
+
+        let capped = board_from_rows(&[well, well, well, well, 0b1111 << 6]);
+        assert!(!can_fill_well(&capped, 9));
+    }
+
+    #[test]
+    fn test_collision_cache_matches_fresh_generate() {
+        let mut tall_rows = [0u16; 18];
+        for (y, row) in tall_rows.iter_mut().enumerate() {
+            *row = FULL_ROW & !(1 << (y * 7 % COL_NB)) & !(1 << ((y + 3) % COL_NB));
+        }
+        let boards = [
+            Board::new(),
+            board_from_rows(&[FULL_ROW & !(0b11 << 3), FULL_ROW & !(0b11 << 4)]),
+            board_from_rows(&tall_rows),
+        ];
+        let raws = |moves: &MoveBuffer| {
+            let mut raws: Vec<u16> = moves.iter().map(|m| m.raw()).collect();
+            raws.sort_unstable();
+            raws
+        };
+        for board in &boards {
+            let mut cache = BoardCollisionCache::new(board);
+            // second pass per piece reads the memoized map
+            for _ in 0..2 {
+                for piece in ALL_PIECES {
+                    let mut fresh = MoveBuffer::new();
+                    generate(board, &mut fresh, piece, true);
+                    let mut cached = MoveBuffer::new();
+                    cache.generate(&mut cached, piece, true);
+                    assert_eq!(raws(&cached), raws(&fresh), "{piece:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_moves_from_map_matches_generate() {
+        let low = board_from_rows(&[FULL_ROW & !(0b11 << 3), FULL_ROW & !(0b11 << 4)]);
+        let tall = board_from_rows(&[0b1000000011; 19]);
+        let raws = |moves: &[Move]| {
+            let mut raws: Vec<u16> = moves.iter().map(|m| m.raw()).collect();
+            raws.sort_unstable();
+            raws
+        };
+        for board in [&low, &tall] {
+            for piece in ALL_PIECES {
+                let mut fresh = MoveBuffer::new();
+                generate(board, &mut fresh, piece, true);
+                let map = CollisionMap::from_board(board, piece);
+                let from_map = generate_moves_from_map(&map, piece, board);
+                assert_eq!(raws(&from_map), raws(fresh.as_slice()), "{piece:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_collision_cache_fills_only_full_height_slots() {
+        let low = board_from_rows(&[FULL_ROW & !(0b11 << 3)]);
+        let mut cache = BoardCollisionCache::new(&low);
+        let mut moves = MoveBuffer::new();
+        for piece in ALL_PIECES {
+            cache.generate(&mut moves, piece, true);
+        }
+        // the low-board O takes the 16-bit generator; spin checks need the map
+        assert!(cache.maps[Piece::O as usize].is_none());
+        assert!(cache.maps[Piece::T as usize].is_some());
+        assert!(cache.maps[Piece::S as usize].is_some());
+
+        let tall = board_from_rows(&[0b1000000011; 19]);
+        let mut cache = BoardCollisionCache::new(&tall);
+        cache.generate(&mut moves, Piece::O, true);
+        assert!(cache.maps[Piece::O as usize].is_some());
+    }
+
+    #[test]
+    fn test_has_clearing_placement() {
+        let one_away = board_from_rows(&[FULL_ROW & !0xF]);
+        assert!(has_clearing_placement(&one_away, Piece::I));
+        // a T covers at most three cells of the row
+        assert!(!has_clearing_placement(&one_away, Piece::T));
+
+        // gaps at both walls: each piece fills at most one of them
+        let two_away = board_from_rows(&[FULL_ROW & !(1 | 1 << 9)]);
+        for piece in ALL_PIECES {
+            assert!(!has_clearing_placement(&two_away, piece), "{piece:?}");
+        }
+        assert!(!has_clearing_placement(&Board::new(), Piece::I));
+    }
 }
@@ -82,10 +82,50 @@ impl GhostMove {
     }
 }
 
+// -- FinessePrefs --
+
+/// Tie-breaks between input paths of the same placement, as finesse
+/// trainers teach them. The default reproduces `get_input`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FinessePrefs {
+    /// Equal-length paths rotate clockwise rather than counter-clockwise.
+    pub(crate) prefer_cw: bool,
+    /// Use a 180 rotation where it is shorter. Off, two quarter turns are
+    /// used instead, falling back to 180 only when nothing else reaches.
+    pub(crate) prefer_flip: bool,
+}
+
+impl Default for FinessePrefs {
+    fn default() -> Self {
+        Self {
+            prefer_cw: true,
+            prefer_flip: true,
+        }
+    }
+}
+
 // -- get_input --
 
 pub(crate) fn get_input(board: &Board, target: &Move, use_finesse: bool, force: bool) -> Inputs {
-    get_input_inner(board, target, use_finesse, force, target.piece())
+    get_input_with_prefs(board, target, use_finesse, force, FinessePrefs::default())
+}
+
+/// `get_input` with rotation tie-breaks from `prefs`.
+pub(crate) fn get_input_with_prefs(
+    board: &Board,
+    target: &Move,
+    use_finesse: bool,
+    force: bool,
+    prefs: FinessePrefs,
+) -> Inputs {
+    let p = target.piece();
+    if !prefs.prefer_flip {
+        let inputs = get_input_inner(board, target, use_finesse, force, p, prefs, false);
+        if inputs.size() > 0 {
+            return inputs;
+        }
+    }
+    get_input_inner(board, target, use_finesse, force, p, prefs, true)
 }
 
 fn get_input_inner(
@@ -94,6 +134,8 @@ fn get_input_inner(
     use_finesse: bool,
     force: bool,
     p: Piece,
+    prefs: FinessePrefs,
+    allow_flip: bool,
 ) -> Inputs {
     let cols = board.compute_cols();
     let cm = CollisionMap::new(&cols, p);
@@ -142,7 +184,7 @@ fn get_input_inner(
 
         // harddrop
         let drop_mask = !((!cm.get(x, rc)) << (63 - y as u32));
-        let drop_y = (clz(drop_mask) as i8) - 1;
+        let drop_y = y + 1 - clz(drop_mask) as i8;
 
         if drop_y >= 0 {
             let mut s = m.s;
@@ -178,13 +220,14 @@ fn get_input_inner(
 
         // rotate
         if p != Piece::O {
-            let dirs = if ACTIVE_RULES.enable_180 { 3 } else { 2 };
-            for d_idx in 0..dirs {
-                let d = match d_idx {
-                    0 => Direction::Cw,
-                    1 => Direction::Ccw,
-                    _ => Direction::Flip,
-                };
+            let dirs = if ACTIVE_RULES.enable_180 && allow_flip { 3 } else { 2 };
+            // BFS order decides ties: the first direction tried wins
+            let order = if prefs.prefer_cw {
+                [Direction::Cw, Direction::Ccw, Direction::Flip]
+            } else {
+                [Direction::Ccw, Direction::Cw, Direction::Flip]
+            };
+            for &d in order.iter().take(dirs) {
                 let input = match d {
                     Direction::Cw => Input::RotateCw,
                     Direction::Ccw => Input::RotateCcw,
@@ -216,7 +259,7 @@ fn get_input_inner(
                         continue;
                     }
                     let x1u = x1 as usize;
-                    if !in_bounds(p, rt, x1 as i32) {
+                    if !in_bounds(p, rt, x1) {
                         continue;
                     }
                     if y1 >= ROW_NB as i32 {
@@ -426,6 +469,21 @@ fn get_input_inner(
     Inputs::new()
 }
 
+// -- finesse --
+
+// input count of the finesse-optimal path to target (DAS = 1 input, hard drop excluded)
+pub(crate) fn finesse_cost(board: &Board, target: &Move) -> Option<usize> {
+    let inputs = get_input(board, target, true, false);
+    inputs.size().checked_sub(1)
+}
+
+// true if the placement used more inputs than the finesse-optimal path.
+// needs the player's recorded count: any stand-in path (e.g. tap-only)
+// would flag correct DAS placements
+pub(crate) fn is_finesse_fault(board: &Board, target: &Move, used_inputs: usize) -> bool {
+    finesse_cost(board, target).is_some_and(|optimal| used_inputs > optimal)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -447,4 +505,63 @@ mod tests {
         assert!(!inputs.data.is_empty());
         assert_eq!(*inputs.data.last().unwrap(), Input::HardDrop);
     }
+
+    #[test]
+    fn test_get_input_hard_drops_from_spawn() {
+        // lands on the floor or on a stack straight from spawn: no soft drop
+        // or detour before the hard drop
+        let target = Move::new(Piece::T, Rotation::North, SPAWN_COL as i32, 0, false);
+        assert_eq!(get_input(&Board::new(), &target, false, false).data, vec![Input::HardDrop]);
+
+        let mut stacked = Board::new();
+        stacked.spawn_garbage(3, 0);
+        let on_stack = Move::new(Piece::T, Rotation::North, SPAWN_COL as i32, 3, false);
+        assert_eq!(get_input(&stacked, &on_stack, false, false).data, vec![Input::HardDrop]);
+    }
+
+    #[test]
+    fn test_finesse_prefs_flip_vs_two_rotations() {
+        let board = Board::new();
+        // T upside down under spawn: one 180 or two quarter turns
+        let target = Move::new(Piece::T, Rotation::South, SPAWN_COL as i32, 1, false);
+        let path = |prefer_cw, prefer_flip| {
+            let prefs = FinessePrefs {
+                prefer_cw,
+                prefer_flip,
+            };
+            get_input_with_prefs(&board, &target, true, false, prefs).data
+        };
+        assert_eq!(path(true, true), vec![Input::RotateFlip, Input::HardDrop]);
+        assert_eq!(
+            path(true, false),
+            vec![Input::RotateCw, Input::RotateCw, Input::HardDrop]
+        );
+        assert_eq!(
+            path(false, false),
+            vec![Input::RotateCcw, Input::RotateCcw, Input::HardDrop]
+        );
+        assert_eq!(get_input(&board, &target, true, false).data, path(true, true));
+    }
+
+    #[test]
+    fn test_finesse_cost_das_to_wall() {
+        let board = Board::new();
+        // T flat against the left wall: one DAS input
+        let target = Move::new(Piece::T, Rotation::North, 1, 0, false);
+        assert_eq!(finesse_cost(&board, &target), Some(1));
+        let next_to_spawn = Move::new(Piece::T, Rotation::North, SPAWN_COL as i32 - 1, 0, false);
+        assert_eq!(finesse_cost(&board, &next_to_spawn), Some(1));
+    }
+
+    #[test]
+    fn test_finesse_fault_flags_extra_inputs() {
+        let board = Board::new();
+        let target = Move::new(Piece::T, Rotation::North, 1, 0, false);
+        assert!(is_finesse_fault(&board, &target, 3));
+        assert!(!is_finesse_fault(&board, &target, 1));
+
+        let near = Move::new(Piece::T, Rotation::North, SPAWN_COL as i32 - 1, 0, false);
+        assert!(!is_finesse_fault(&board, &near, 1));
+        assert!(is_finesse_fault(&board, &near, 2));
+    }
 }
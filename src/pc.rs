@@ -0,0 +1,230 @@
+// pc.rs -- perfect-clear enumeration: depth-first over the queue with hold,
+// every placement kept under a fixed clear height
+
+use std::collections::HashSet;
+
+use crate::board::{Board, FULL_ROW};
+use crate::header::*;
+use crate::move_buffer::MoveBuffer;
+use crate::movegen::{footprint, generate_with, MoveGenConfig};
+
+/// Tallest perfect clear tried; anything higher needs far more pieces than a
+/// preview shows.
+pub const PC_MAX_HEIGHT: usize = 6;
+
+// (piece, cells) per placement, cells in the starting board's rows, sorted:
+// the same placements in any order or rotation name are one solution
+type Signature = Vec<(u8, [(i32, i32); 4])>;
+
+struct Solver<'a> {
+    queue: &'a [Piece],
+    limit: usize,
+    seen: HashSet<Signature>,
+    solutions: Vec<Vec<Move>>,
+    // starting-board row of each current row, updated as lines clear
+    rows: Vec<i32>,
+    placed: Signature,
+}
+
+/// Up to `limit` distinct perfect clears of `board` using pieces from
+/// `queue` (first entry is the current piece) and `hold`. Each solution is
+/// the move list in play order; a solution may leave queue pieces unused.
+/// Heights are tried lowest first, up to `PC_MAX_HEIGHT`. An already empty
+/// board yields no solutions.
+pub fn solve_pc_all(
+    board: &Board,
+    queue: &[Piece],
+    hold: Option<Piece>,
+    limit: usize,
+) -> Vec<Vec<Move>> {
+    let mut solver = Solver {
+        queue,
+        limit,
+        seen: HashSet::new(),
+        solutions: Vec::new(),
+        rows: Vec::new(),
+        placed: Vec::new(),
+    };
+    if limit == 0 || board.is_empty() {
+        return solver.solutions;
+    }
+
+    let pieces = queue.len() + hold.is_some() as usize;
+    let cells = board.total_cells() as usize;
+    for height in (board.height() as usize).max(1)..=PC_MAX_HEIGHT {
+        let empty = height * COL_NB - cells;
+        if !empty.is_multiple_of(4) || empty / 4 > pieces {
+            continue;
+        }
+        let mut path = Vec::with_capacity(empty / 4);
+        solver.rows = (0..height as i32).collect();
+        solver.search(board, 0, hold, height, &mut path);
+        if solver.solutions.len() >= limit {
+            break;
+        }
+    }
+    solver.solutions
+}
+
+impl Solver<'_> {
+    // `next` indexes the current piece in `queue`
+    fn search(
+        &mut self,
+        board: &Board,
+        next: usize,
+        hold: Option<Piece>,
+        height: usize,
+        path: &mut Vec<Move>,
+    ) {
+        if self.solutions.len() >= self.limit {
+            return;
+        }
+        if board.is_empty() {
+            self.record(path);
+            return;
+        }
+        let remaining = self.queue.len() - next.min(self.queue.len()) + hold.is_some() as usize;
+        let empty = height * COL_NB - board.total_cells() as usize;
+        if empty / 4 > remaining {
+            return;
+        }
+
+        let Some(&current) = self.queue.get(next) else {
+            // only the held piece is left
+            if let Some(held) = hold {
+                self.place(board, held, next, None, height, path);
+            }
+            return;
+        };
+        self.place(board, current, next + 1, hold, height, path);
+        match hold {
+            Some(held) if held != current => {
+                self.place(board, held, next + 1, Some(current), height, path);
+            }
+            None => {
+                if let Some(&after) = self.queue.get(next + 1) {
+                    if after != current {
+                        self.place(board, after, next + 2, Some(current), height, path);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn place(
+        &mut self,
+        board: &Board,
+        piece: Piece,
+        next: usize,
+        hold: Option<Piece>,
+        height: usize,
+        path: &mut Vec<Move>,
+    ) {
+        let config = MoveGenConfig {
+            ceiling: height,
+            ..MoveGenConfig::default()
+        };
+        let mut moves = MoveBuffer::new();
+        generate_with(board, &mut moves, piece, true, &config);
+
+        let mut tried = HashSet::new();
+        for m in moves.as_slice() {
+            let cells = footprint(m);
+            if !board.legal_lock_placement(m) || !tried.insert(cells) {
+                continue;
+            }
+            let mut placed = cells;
+            for cell in placed.iter_mut() {
+                cell.1 = self.rows[cell.1 as usize];
+            }
+            let mut cleared_rows = 0u64;
+            for &(_, y) in &cells {
+                let row = cells
+                    .iter()
+                    .filter(|c| c.1 == y)
+                    .fold(board.rows[y as usize], |row, c| row | 1 << c.0);
+                if row == FULL_ROW {
+                    cleared_rows |= 1 << y;
+                }
+            }
+
+            let mut after = board.clone();
+            let cleared = after.do_move(m) as usize;
+            let saved_rows = self.rows.clone();
+            for y in (0..height).rev().filter(|y| cleared_rows >> y & 1 != 0) {
+                self.rows.remove(y);
+            }
+            path.push(*m);
+            self.placed.push((piece as u8, placed));
+            self.search(&after, next, hold, height - cleared, path);
+            self.placed.pop();
+            path.pop();
+            self.rows = saved_rows;
+            if self.solutions.len() >= self.limit {
+                return;
+            }
+        }
+    }
+
+    fn record(&mut self, path: &[Move]) {
+        let mut signature = self.placed.clone();
+        for (_, cells) in signature.iter_mut() {
+            cells.sort_unstable();
+        }
+        signature.sort_unstable();
+        if self.seen.insert(signature) {
+            self.solutions.push(path.to_vec());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BOARD_HEIGHT;
+
+    fn replay(board: &Board, moves: &[Move]) -> Board {
+        let mut b = board.clone();
+        for m in moves {
+            assert!(b.legal_lock_placement(m));
+            b.do_move(m);
+        }
+        b
+    }
+
+    #[test]
+    fn test_solve_pc_all_finds_every_fill() {
+        // 6x2 pocket on the left: the O goes at either end of two stacked
+        // I's. Playing O between the I's (clearing a row) is the same
+        // solution and must not count twice
+        let pocket = Board::from(&{
+            let mut rows = [0u16; BOARD_HEIGHT];
+            rows[..2].fill(FULL_ROW & !0x3F);
+            rows
+        });
+        let queue = [Piece::I, Piece::I, Piece::O];
+        let solutions = solve_pc_all(&pocket, &queue, None, 10);
+        assert_eq!(solutions.len(), 2);
+        for solution in &solutions {
+            assert!(replay(&pocket, solution).is_empty());
+        }
+
+        assert_eq!(solve_pc_all(&pocket, &queue, None, 1).len(), 1);
+        assert!(solve_pc_all(&pocket, &[Piece::S, Piece::Z], None, 10).is_empty());
+        assert!(solve_pc_all(&Board::new(), &queue, None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_solve_pc_all_uses_hold() {
+        let pocket = Board::from(&{
+            let mut rows = [0u16; BOARD_HEIGHT];
+            rows[..2].fill(FULL_ROW & !0x3);
+            rows
+        });
+        assert!(solve_pc_all(&pocket, &[Piece::T], None, 10).is_empty());
+        let solutions = solve_pc_all(&pocket, &[Piece::T], Some(Piece::O), 10);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0][0].piece(), Piece::O);
+    }
+}
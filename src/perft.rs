@@ -46,6 +46,32 @@ pub fn perft(board: &Board, queue_offset: usize, depth: usize) -> u64 {
     nodes
 }
 
+/// node count with `first` at the root, then the fixed QUEUE tail from index 1;
+/// the I entry equals `perft(board, 0, depth)`
+pub fn perft_signature(board: &Board, depth: usize) -> Vec<(Piece, u64)> {
+    ALL_PIECES
+        .iter()
+        .map(|&first| {
+            if depth == 0 {
+                return (first, 1);
+            }
+            let ml = MoveList::new(board, first);
+            let nodes = if depth == 1 {
+                ml.size() as u64
+            } else {
+                ml.iter()
+                    .map(|m| {
+                        let mut child = board.clone();
+                        child.do_move(m);
+                        perft(&child, 1, depth - 1)
+                    })
+                    .sum()
+            };
+            (first, nodes)
+        })
+        .collect()
+}
+
 /// divide: print per-move breakdown at root
 pub fn divide(board: &Board, depth: usize) -> u64 {
     let piece = queue_piece(0);
@@ -107,6 +133,24 @@ pub fn perft_parallel(board: &Board, depth: usize) -> u64 {
     }
 }
 
+/// legal move count for `piece` on each board; parallel when rayon is enabled
+pub fn count_moves_batch(boards: &[Board], piece: Piece) -> Vec<usize> {
+    #[cfg(feature = "rayon")]
+    {
+        boards
+            .par_iter()
+            .map(|b| MoveList::new(b, piece).size())
+            .collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        boards
+            .iter()
+            .map(|b| MoveList::new(b, piece).size())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +190,45 @@ mod tests {
         let b = Board::new();
         assert_eq!(perft(&b, 0, 5), D5);
     }
+
+    #[test]
+    fn test_count_moves_batch_matches_serial() {
+        let mut boards = vec![Board::new()];
+        for hole in [0, 4, 9] {
+            let mut b = Board::new();
+            b.spawn_garbage(3, hole);
+            b.do_move(&Move::new(Piece::T, Rotation::North, 5, 4, false));
+            boards.push(b);
+        }
+
+        for piece in ALL_PIECES {
+            let counts = count_moves_batch(&boards, piece);
+            let expected: Vec<usize> = boards
+                .iter()
+                .map(|b| MoveList::new(b, piece).size())
+                .collect();
+            assert_eq!(counts, expected, "{piece:?}");
+        }
+        assert_eq!(count_moves_batch(&boards[..1], Piece::I), vec![D1 as usize]);
+        assert!(count_moves_batch(&[], Piece::T).is_empty());
+    }
+
+    #[test]
+    fn test_perft_signature_empty_d2() {
+        // tail piece is O for every root
+        let sig = perft_signature(&Board::new(), 2);
+        assert_eq!(
+            sig,
+            vec![
+                (Piece::I, D2),
+                (Piece::O, 81),
+                (Piece::T, 306),
+                (Piece::L, 313),
+                (Piece::J, 313),
+                (Piece::S, 153),
+                (Piece::Z, 153),
+            ]
+        );
+        assert!(perft_signature(&Board::new(), 0).iter().all(|&(_, n)| n == 1));
+    }
 }
@@ -6,3 +6,64 @@ pub struct Rules {
     pub srs_plus: bool,
     pub spawn_row: i32,
 }
+
+/// Which pieces can score spins.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SpinRule {
+    /// Only T placements carry a spin; other pieces always lock as `NoSpin`.
+    TSpinOnly,
+    /// Non-T pieces that lock immobile after a rotation are `Mini`.
+    #[default]
+    AllMini,
+    /// Every piece can spin, with full spins for non-T pieces. `Move` has no
+    /// encoding for a full non-T spin yet, so this currently generates the
+    /// same moves as `AllMini`.
+    AllSpinPlus,
+}
+
+impl Rules {
+    /// Spin rule implied by the `enable_allspin` flag.
+    pub const fn spin_rule(&self) -> SpinRule {
+        if self.enable_allspin {
+            SpinRule::AllMini
+        } else {
+            SpinRule::TSpinOnly
+        }
+    }
+}
+
+/// Frame timing around a lock, at 60 frames per second. TETR.IO league play
+/// uses no delays, which is the default.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GravityConfig {
+    /// Entry delay (ARE): frames from a lock to the next spawn.
+    pub placement_delay: u32,
+    /// Extra frames a lock that clears lines spends on the clear animation.
+    pub line_clear_delay: u32,
+}
+
+/// Frames a placement clearing `lines` rows consumes before the next piece
+/// spawns, clear animation included.
+pub fn frames_for_placement(lines: u8, config: &GravityConfig) -> u32 {
+    if lines == 0 {
+        config.placement_delay
+    } else {
+        config.placement_delay + config.line_clear_delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_for_placement_adds_clear_delay() {
+        let config = GravityConfig {
+            placement_delay: 6,
+            line_clear_delay: 40,
+        };
+        assert_eq!(frames_for_placement(0, &config), 6);
+        assert_eq!(frames_for_placement(4, &config), 46);
+        assert_eq!(frames_for_placement(4, &GravityConfig::default()), 0);
+    }
+}
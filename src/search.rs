@@ -1,16 +1,22 @@
 // search.rs -- beam search with hold for coaching engine
 // expands moves breadth-first, pruned to beam_width at each depth
 
-use crate::bag;
-
-use crate::eval::EvalWeights;
+use crate::attack::{calculate_attack_full, AttackConfig, AttackContext};
+use crate::bag::{self, SevenBag};
+use crate::board::{Board, VISIBLE_HEIGHT};
+use crate::eval::{count_holes, project_garbage, surface_bumpiness, EvalWeights, Evaluator};
+use crate::move_buffer::MoveBuffer;
+use crate::movegen::{can_fill_well, footprint, generate};
+use crate::header::{Move, Piece};
 
 use crate::state::GameState;
 use crate::transposition::{get_zobrist_keys, TranspositionTable, DEFAULT_TT_SIZE};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{Duration, Instant};
 
-pub use crate::search_config::{SearchConfig, SearchNode, SearchResult, SearchResultFull};
+pub use crate::search_config::{
+    Objective, SearchConfig, SearchNode, SearchResult, SearchResultFull, MAX_SEARCH_DEPTH,
+};
 pub(crate) use crate::search_config::{SearchExpansionContext, SearchIterationParams};
 pub(crate) use crate::search_expand::{expand_node, gen_and_eval_root};
 
@@ -19,7 +25,7 @@ pub(crate) use crate::search_expand::{expand_node, gen_and_eval_root};
 pub fn find_best_move(
     state: &GameState,
     config: &SearchConfig,
-    weights: &EvalWeights,
+    weights: &dyn Evaluator,
 ) -> Option<SearchResult> {
     find_best_move_with_scores(state, config, weights).map(|full| full.best)
 }
@@ -27,7 +33,7 @@ pub fn find_best_move(
 pub fn find_best_move_with_scores(
     state: &GameState,
     config: &SearchConfig,
-    weights: &EvalWeights,
+    weights: &dyn Evaluator,
 ) -> Option<SearchResultFull> {
     find_best_move_with_scores_forced(state, config, weights, None)
 }
@@ -39,13 +45,15 @@ pub fn find_best_move_with_scores(
 pub fn find_best_move_with_scores_forced(
     state: &GameState,
     config: &SearchConfig,
-    weights: &EvalWeights,
+    weights: &dyn Evaluator,
     forced_root_move: Option<crate::header::Move>,
 ) -> Option<SearchResultFull> {
+    let preview_len = config.max_preview.map_or(state.queue.len(), |n| n.min(state.queue.len()));
+    let preview = &state.queue[..preview_len];
     let search_queue = if config.extend_queue_7bag {
-        bag::extend_queue(&state.queue, state.current, state.hold)
+        bag::extend_queue(preview, state.current, state.hold)
     } else {
-        state.queue.clone()
+        preview.to_vec()
     };
 
     let max_depth = config.depth.min(search_queue.len() + 1);
@@ -147,6 +155,495 @@ pub fn find_best_move_with_scores_forced(
     best_full
 }
 
+/// Best root move that leaves `avoid_column` untouched, e.g. to keep a well open.
+/// Returns None when every legal placement has to touch that column.
+pub fn find_best_move_avoiding(
+    state: &GameState,
+    config: &SearchConfig,
+    weights: &EvalWeights,
+    avoid_column: usize,
+) -> Option<(Move, f32)> {
+    let config = SearchConfig {
+        avoid_column: Some(avoid_column),
+        ..config.clone()
+    };
+    find_best_move(state, &config, weights).map(|r| (r.best_move, r.score))
+}
+
+/// One-ply best move for `piece` under each weight set; Some((under_a,
+/// under_b)) only when they differ. For finding which weight edit flipped a choice.
+pub fn explain_choice_change(
+    board: &Board,
+    piece: Piece,
+    a: &EvalWeights,
+    b: &EvalWeights,
+) -> Option<(Move, Move)> {
+    let state = GameState::new(board.clone(), piece, Vec::new());
+    let config = SearchConfig {
+        depth: 1,
+        extend_queue_7bag: false,
+        ..SearchConfig::default()
+    };
+    let best_a = find_best_move(&state, &config, a)?.best_move;
+    let best_b = find_best_move(&state, &config, b)?.best_move;
+    (best_a.raw() != best_b.raw()).then_some((best_a, best_b))
+}
+
+/// One-ply best landing of `piece` in each rotation, indexed by
+/// `Rotation as usize`; None where a rotation has no legal placement.
+/// For placement-picker previews.
+pub fn best_per_rotation(
+    board: &Board,
+    piece: Piece,
+    weights: &EvalWeights,
+) -> [Option<(Move, f32)>; 4] {
+    let state = GameState::new(board.clone(), piece, Vec::new());
+    let config = SearchConfig {
+        depth: 1,
+        extend_queue_7bag: false,
+        ..SearchConfig::default()
+    };
+    let mut best: [Option<(Move, f32)>; 4] = [None; 4];
+    let Some(full) = find_best_move_with_scores(&state, &config, weights) else {
+        return best;
+    };
+    for (m, score) in full.root_scores {
+        let slot = &mut best[m.rotation() as usize];
+        if slot.is_none_or(|(_, s)| score > s) {
+            *slot = Some((m, score));
+        }
+    }
+    best
+}
+
+/// One-ply best placement of `piece` that clears exactly `exact_lines`
+/// rows, scored like `best_per_rotation`. None if no placement clears that
+/// many. For drills ("best single here?").
+pub fn best_move_clearing(
+    board: &Board,
+    piece: Piece,
+    exact_lines: u8,
+    weights: &EvalWeights,
+) -> Option<(Move, f32)> {
+    let state = GameState::new(board.clone(), piece, Vec::new());
+    let config = SearchConfig {
+        depth: 1,
+        extend_queue_7bag: false,
+        ..SearchConfig::default()
+    };
+    let full = find_best_move_with_scores(&state, &config, weights)?;
+    full.root_scores
+        .into_iter()
+        .filter(|(m, _)| board.clone().do_move(m) == exact_lines as i32)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Totals from one self-play game (see `play_game`).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GameStats {
+    pub pieces: usize,
+    pub lines: u32,
+    /// Clears by line count; quads are the remaining `lines`.
+    pub singles: u32,
+    pub doubles: u32,
+    pub triples: u32,
+    pub attack: f32,
+    pub max_combo: u32,
+    pub max_b2b: u8,
+    pub topped_out: bool,
+    /// Holes (see `count_holes`) on the board after the last piece.
+    pub holes: i32,
+    /// `attack / lines` (lines floored at 1); kept current by `record`.
+    pub attack_per_line: f32,
+    /// Placements that took more inputs than `finesse_cost`; only replay
+    /// analysis fills this in, from frames that recorded their inputs.
+    pub finesse_faults: u32,
+}
+
+impl GameStats {
+    /// Fold in one locked piece; `combo`/`b2b` are the values after it.
+    pub fn record(&mut self, lines: u8, attack: f32, combo: u32, b2b: u8) {
+        self.record_clear(lines);
+        self.attack += attack;
+        self.max_combo = self.max_combo.max(combo);
+        self.max_b2b = self.max_b2b.max(b2b);
+        self.attack_per_line = self.attack / self.lines.max(1) as f32;
+    }
+
+    /// `record` for the line counts alone, when attack and chains aren't known.
+    pub fn record_clear(&mut self, lines: u8) {
+        self.pieces += 1;
+        self.lines += lines as u32;
+        match lines {
+            1 => self.singles += 1,
+            2 => self.doubles += 1,
+            3 => self.triples += 1,
+            _ => {}
+        }
+    }
+}
+
+const PLAY_PREVIEWS: usize = 5;
+
+/// Self-play with a seeded 7-bag until top-out or `max_pieces`.
+/// Uses a shallow search so it is cheap enough for a tuning fitness loop.
+pub fn play_game(weights: &EvalWeights, seed: u64, max_pieces: usize) -> GameStats {
+    let config = SearchConfig {
+        beam_width: 200,
+        depth: 3,
+        ..SearchConfig::default()
+    };
+    play_game_with(&config, weights, seed, max_pieces)
+}
+
+pub fn play_game_with(
+    config: &SearchConfig,
+    weights: &EvalWeights,
+    seed: u64,
+    max_pieces: usize,
+) -> GameStats {
+    let mut bag = SevenBag::new(seed);
+    let current = bag.next_piece();
+    let queue = (0..PLAY_PREVIEWS).map(|_| bag.next_piece()).collect();
+    let mut state = GameState::new(Board::new(), current, queue);
+    let mut stats = GameStats::default();
+
+    while stats.pieces < max_pieces {
+        let Some(result) = find_best_move(&state, config, weights) else {
+            stats.topped_out = true;
+            break;
+        };
+        let mv = result.best_move;
+
+        if result.hold_used {
+            // holding into an empty slot consumes the next preview
+            let refill = state.hold.is_none();
+            if let Some(swapped) = state.with_hold_swap() {
+                state = swapped;
+            }
+            if refill {
+                state.queue.push(bag.next_piece());
+            }
+        }
+
+        let mut board = state.board.clone();
+        let lines = board.do_move(&mv) as u8;
+        let attack = placement_attack(&state, &mv, &board, lines, &config.attack_config);
+
+        let blocked = GameState::spawn_envelope_blocked(&board);
+        state.apply_move_transition(&mv, lines, result.hold_used, board.height(), blocked);
+        state.board = board;
+        state.pop_next();
+        state.queue.push(bag.next_piece());
+
+        stats.record(lines, attack, state.combo, state.b2b);
+        stats.holes = count_holes(&state.board);
+
+        if state.check_game_over(&mv).is_some() {
+            stats.topped_out = true;
+            break;
+        }
+    }
+
+    stats
+}
+
+const SURVIVAL_BEAM: usize = 200;
+const SURVIVAL_DEPTH: usize = 3;
+
+/// Whether `board` survives tanking `incoming` lines of clean garbage (hole
+/// in column 0, all received before the first piece) and then placing every
+/// piece of `pieces` in order, no hold. Each piece is chosen by a shallow
+/// search that refuses hidden-buffer locks; any top-out fails.
+pub fn survival_probability(
+    board: &Board,
+    incoming: u8,
+    pieces: &[Piece],
+    weights: &EvalWeights,
+) -> bool {
+    let board = project_garbage(board, incoming, 0);
+    if board.height() as usize > VISIBLE_HEIGHT {
+        return false;
+    }
+    let Some((&first, rest)) = pieces.split_first() else {
+        return true;
+    };
+    let config = SearchConfig {
+        beam_width: SURVIVAL_BEAM,
+        depth: SURVIVAL_DEPTH,
+        extend_queue_7bag: false,
+        avoid_topout: true,
+        root_hold: false,
+        ..SearchConfig::default()
+    };
+
+    let mut state = GameState::new(board, first, rest.to_vec());
+    for _ in pieces {
+        let Some(result) = find_best_move(&state, &config, weights) else {
+            return false;
+        };
+        let mv = result.best_move;
+        let mut board = state.board.clone();
+        let lines = board.do_move(&mv) as u8;
+        let blocked = GameState::spawn_envelope_blocked(&board);
+        state.apply_move_transition(&mv, lines, false, board.height(), blocked);
+        state.board = board;
+        state.pop_next();
+        if state.check_game_over(&mv).is_some() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Attack sent by locking `mv` from `state`; `board_after` and `lines` are
+/// the result of `do_move`. Pending garbage is not modelled.
+pub(crate) fn placement_attack(
+    state: &GameState,
+    mv: &Move,
+    board_after: &Board,
+    lines: u8,
+    attack_config: &AttackConfig,
+) -> f32 {
+    let (next_b2b, next_combo) = GameState::next_chain_values(state.b2b, state.combo, mv, lines);
+    let b2b_broken_from = (state.b2b >= 4 && next_b2b == 0 && lines > 0).then_some(state.b2b);
+    calculate_attack_full(&AttackContext {
+        lines,
+        spin: mv.spin(),
+        b2b: next_b2b,
+        combo: next_combo as u8,
+        config: attack_config,
+        is_perfect_clear: board_after.is_empty(),
+        b2b_broken_from,
+        clears_garbage: false,
+    })
+}
+
+/// Placement of `piece` sending the most attack from the given chain state
+/// (lines, spin, perfect clear; no lookahead). None if `piece` can't lock.
+pub fn max_attack_move(
+    board: &Board,
+    piece: Piece,
+    b2b: u8,
+    combo: u8,
+    config: &AttackConfig,
+) -> Option<(Move, f32)> {
+    let mut state = GameState::new(board.clone(), piece, Vec::new());
+    state.b2b = b2b;
+    state.combo = combo as u32;
+    let mut moves = MoveBuffer::new();
+    generate(board, &mut moves, piece, true);
+
+    let mut best: Option<(Move, f32)> = None;
+    for m in moves.iter().filter(|m| board.legal_lock_placement(m)) {
+        let mut after = board.clone();
+        let lines = after.do_move(m) as u8;
+        let attack = placement_attack(&state, m, &after, lines, config);
+        if best.is_none_or(|(_, a)| attack > a) {
+            best = Some((*m, attack));
+        }
+    }
+    best
+}
+
+/// Placement of `piece` leaving the flattest surface (`surface_bumpiness`
+/// after line clears), with that bumpiness; earliest generated move on ties.
+/// None if `piece` can't lock.
+pub fn min_resulting_bumpiness(board: &Board, piece: Piece) -> Option<(Move, u32)> {
+    let mut moves = MoveBuffer::new();
+    generate(board, &mut moves, piece, true);
+
+    let mut best: Option<(Move, u32)> = None;
+    for m in moves.iter().filter(|m| board.legal_lock_placement(m)) {
+        let mut after = board.clone();
+        after.do_move(m);
+        let bump = surface_bumpiness(&after);
+        if best.is_none_or(|(_, b)| bump < b) {
+            best = Some((*m, bump));
+        }
+    }
+    best
+}
+
+const DOWNSTACK_BEAM: usize = 64;
+const DOWNSTACK_LINE: f32 = 10.0;
+const DOWNSTACK_HOLE: f32 = 25.0;
+const DOWNSTACK_HEIGHT: f32 = 0.1;
+
+struct DownstackNode {
+    board: Board,
+    path: Vec<Move>,
+    lines: u32,
+    score: f32,
+}
+
+/// Downstack-only search over `piece` then `queue` (no hold): most lines
+/// cleared per piece, heavily penalising holes beyond the starting count.
+/// Returns the shortest prefix achieving the best rate, or None if `piece`
+/// has no legal placement.
+pub fn find_downstack(board: &Board, piece: Piece, queue: &[Piece]) -> Option<Vec<Move>> {
+    let start_holes = count_holes(board);
+    let objective = |b: &Board, lines: u32| {
+        DOWNSTACK_LINE * lines as f32
+            - DOWNSTACK_HOLE * (count_holes(b) - start_holes) as f32
+            - DOWNSTACK_HEIGHT * b.height() as f32
+    };
+
+    let mut beam = vec![DownstackNode {
+        board: board.clone(),
+        path: Vec::new(),
+        lines: 0,
+        score: 0.0,
+    }];
+    let mut best: Option<(f32, Vec<Move>)> = None;
+
+    for p in std::iter::once(piece).chain(queue.iter().copied()) {
+        let mut next = Vec::new();
+        for node in &beam {
+            let mut moves = MoveBuffer::new();
+            generate(&node.board, &mut moves, p, false);
+            for m in moves.as_slice() {
+                if !node.board.legal_lock_placement(m) {
+                    continue;
+                }
+                let mut child = node.board.clone();
+                let lines = node.lines + child.do_move(m) as u32;
+                let score = objective(&child, lines);
+                let mut path = node.path.clone();
+                path.push(*m);
+                next.push(DownstackNode {
+                    board: child,
+                    path,
+                    lines,
+                    score,
+                });
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        next.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+        next.truncate(DOWNSTACK_BEAM);
+
+        // rate: same objective with lines normalised per piece placed
+        for node in &next {
+            let pieces = node.path.len() as f32;
+            let rate = node.score - DOWNSTACK_LINE * node.lines as f32
+                + DOWNSTACK_LINE * node.lines as f32 / pieces;
+            if best.as_ref().is_none_or(|(r, _)| rate > *r) {
+                best = Some((rate, node.path.clone()));
+            }
+        }
+        beam = next;
+    }
+
+    best.map(|(_, path)| path)
+}
+
+/// Distinct boards (by `content_hash`, after line clears) reachable from
+/// `board` by placing the first 1..=`k` pieces of `queue` in order, no hold.
+/// Breadth-first, shallowest first; `k` is capped at the queue length.
+/// Growth is exponential (roughly 30+ placements per piece), so keep `k`
+/// to a handful of pieces.
+pub fn reachable_boards(board: &Board, queue: &[Piece], k: usize) -> Vec<Board> {
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    let mut frontier = vec![board.clone()];
+    for &piece in queue.iter().take(k) {
+        let mut next = Vec::new();
+        for b in &frontier {
+            let mut moves = MoveBuffer::new();
+            generate(b, &mut moves, piece, true);
+            for m in moves.as_slice() {
+                if !b.legal_lock_placement(m) {
+                    continue;
+                }
+                let mut child = b.clone();
+                child.do_move(m);
+                if seen.insert(child.content_hash()) {
+                    next.push(child);
+                }
+            }
+        }
+        out.extend(next.iter().cloned());
+        frontier = next;
+    }
+    out
+}
+
+/// Furthest queue position an I may be for the well to be worth holding open.
+pub const WELL_KEEP_HORIZON: usize = 6;
+
+/// Whether to hold a well open for the next I (see `well_plan`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WellPlan {
+    /// Keep stacking around the well; false means filling it (e.g. a triple
+    /// with a vertical J/L) is fine.
+    pub keep_well: bool,
+    /// Queue index of the next I, if the queue shows one.
+    pub i_distance: Option<usize>,
+}
+
+/// Keep the well in `well_column` when an I shows within `WELL_KEEP_HORIZON`
+/// queue pieces and can still drop to the well's bottom (`can_fill_well`).
+pub fn well_plan(board: &Board, queue: &[Piece], well_column: usize) -> WellPlan {
+    let i_distance = queue.iter().position(|&p| p == Piece::I);
+    let keep_well = i_distance.is_some_and(|d| d <= WELL_KEEP_HORIZON)
+        && can_fill_well(board, well_column);
+    WellPlan {
+        keep_well,
+        i_distance,
+    }
+}
+
+/// Place `mv` on a copy of `board` and clear lines.
+/// Returns the resulting board and the number of lines cleared.
+pub fn apply_move(board: &Board, mv: &Move) -> (Board, u8) {
+    let (result, lines, _) = apply_move_verbose(board, mv);
+    (result, lines)
+}
+
+/// `apply_move` that also returns the cleared-row mask (bit y = row y
+/// before the clear). Illegal placements leave the board unchanged.
+pub fn apply_move_verbose(board: &Board, mv: &Move) -> (Board, u8, u64) {
+    let mut result = board.clone();
+    if !result.legal_lock_placement(mv) {
+        return (result, 0, 0);
+    }
+    result.place(mv);
+    let mask = result.clear_lines_mask();
+    (result, mask.count_ones() as u8, mask)
+}
+
+/// Cells of `mv` as (x, y) after its line clears shift the board down, with
+/// the cleared-row mask from `apply_move_verbose`. Cells on cleared rows are
+/// dropped; an illegal placement gives no cells.
+pub fn placed_cells_after_clear(board_before: &Board, mv: &Move) -> (Vec<(usize, usize)>, u64) {
+    if !board_before.legal_lock_placement(mv) {
+        return (Vec::new(), 0);
+    }
+    let (_, _, mask) = apply_move_verbose(board_before, mv);
+    let cells = footprint(mv)
+        .iter()
+        .filter(|&&(_, y)| mask >> y & 1 == 0)
+        .map(|&(x, y)| {
+            let below = (mask & ((1u64 << y) - 1)).count_ones() as i32;
+            (x as usize, (y - below) as usize)
+        })
+        .collect();
+    (cells, mask)
+}
+
+/// Keep only `piece`'s placements that stay fully inside the visible field.
+/// A mino locked in the hidden buffer is treated as a loss.
+pub fn filters_topout(moves: &[Move], piece: Piece) -> Vec<Move> {
+    moves
+        .iter()
+        .copied()
+        .filter(|m| m.piece() == piece && !m.tops_out())
+        .collect()
+}
+
 fn run_beam_search_iteration(params: &mut SearchIterationParams<'_>) -> Option<SearchResultFull> {
     let mut ctx = SearchExpansionContext {
         config: params.config,
@@ -167,6 +664,9 @@ fn run_beam_search_iteration(params: &mut SearchIterationParams<'_>) -> Option<S
         params.forced_root_move,
     );
     beam.sort_unstable_by(compare_nodes_desc);
+    if params.config.diversity_prune {
+        prune_similar(&mut beam, params.forced_root_move);
+    }
     truncate_with_forced(&mut beam, params.beam_width, params.forced_root_move);
 
     for depth_idx in 0..params.max_depth.saturating_sub(1) {
@@ -217,6 +717,9 @@ fn run_beam_search_iteration(params: &mut SearchIterationParams<'_>) -> Option<S
             params.forced_root_move,
         );
         next_beam.sort_unstable_by(compare_nodes_desc);
+        if params.config.diversity_prune {
+            prune_similar(&mut next_beam, params.forced_root_move);
+        }
         truncate_with_forced(&mut next_beam, params.beam_width, params.forced_root_move);
         beam = next_beam;
     }
@@ -339,6 +842,17 @@ fn compute_position_complexity(root_scores: &[(crate::header::Move, f32)]) -> f3
     variance
 }
 
+/// On a sorted beam, keep only the best node per board surface profile so
+/// near-duplicates don't crowd out different shapes. Forced-root nodes are
+/// always kept.
+fn prune_similar(beam: &mut Vec<SearchNode>, forced: Option<crate::header::Move>) {
+    let mut seen = std::collections::HashSet::with_capacity(beam.len());
+    beam.retain(|n| {
+        forced.is_some_and(|fm| n.root_move.raw() == fm.raw())
+            || seen.insert(n.board.surface_profile())
+    });
+}
+
 /// Truncate beam to `max_size`, but if a forced root move would be truncated,
 /// re-insert it by evicting the worst node.
 fn truncate_with_forced(
@@ -468,6 +982,9 @@ fn expand_root(state: &GameState, ctx: &mut SearchExpansionContext<'_>) -> Vec<S
     let mut nodes = Vec::with_capacity(128);
 
     gen_and_eval_root(state, state.current, state.hold, false, ctx, &mut nodes);
+    if !ctx.config.root_hold {
+        return nodes;
+    }
 
     match state.hold {
         Some(held) if held != state.current => {
@@ -489,10 +1006,19 @@ fn expand_root(state: &GameState, ctx: &mut SearchExpansionContext<'_>) -> Vec<S
 mod tests {
     use super::*;
     use crate::bag;
-    use crate::board::{Board, FULL_ROW};
-    use crate::header::{Move, Piece, COL_NB};
-    use crate::state::CoachingState;
+    use crate::board::{BOARD_HEIGHT, FULL_ROW};
+    use crate::attack::AttackConfig;
+    use crate::header::{Rotation, SpinType, ALL_PIECES, COL_NB};
+    use crate::state::{ClearEvent, CoachingState};
     use smallvec::{smallvec, SmallVec};
+
+    // `Board::from` for the bottom rows only
+    fn board_from_rows(rows: &[u16]) -> Board {
+        let mut all = [0u16; BOARD_HEIGHT];
+        all[..rows.len()].copy_from_slice(rows);
+        Board::from(&all)
+    }
+
     fn make_node(
         score: f32,
         fatality: crate::state::FatalityState,
@@ -526,6 +1052,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_game_stats_counts_clear_types() {
+        let mut stats = GameStats::default();
+        for lines in [1, 0, 2, 3, 0, 4] {
+            stats.record_clear(lines);
+        }
+        assert_eq!((stats.singles, stats.doubles, stats.triples), (1, 1, 1));
+        assert_eq!(stats.lines, 10);
+        assert_eq!(stats.pieces, 6);
+    }
+
     #[test]
     fn test_find_best_move_empty_board() {
         let state = GameState::new(Board::new(), Piece::T, vec![Piece::I, Piece::O]);
@@ -603,6 +1140,183 @@ mod tests {
         assert!(result.is_some(), "should find a move with hold available");
     }
 
+    #[test]
+    fn test_root_hold_branch_wins_on_tetris_ready_board() {
+        let board = board_from_rows(&[FULL_ROW & !(1 << 9); 4]);
+        let config = SearchConfig {
+            beam_width: 200,
+            depth: 1,
+            extend_queue_7bag: false,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+
+        let mut with_hold = GameState::new(board.clone(), Piece::O, Vec::new());
+        with_hold.hold = Some(Piece::I);
+        let held = find_best_move(&with_hold, &config, &weights)
+            .unwrap_or_else(|| panic!("O and I both fit"));
+        assert!(held.hold_used);
+        assert_eq!(held.best_move.piece(), Piece::I);
+
+        let no_hold = GameState::new(board, Piece::O, Vec::new());
+        let played = find_best_move(&no_hold, &config, &weights)
+            .unwrap_or_else(|| panic!("O fits"));
+        assert!(!played.hold_used);
+        assert!(held.score > played.score);
+    }
+
+    #[test]
+    fn test_best_move_clearing_single_vs_tetris() {
+        // well at column 9 under a row that an I on the right completes
+        let well = FULL_ROW & !(1 << 9);
+        let board = board_from_rows(&[well, well, well, well, 0x3F]);
+        let weights = EvalWeights::default();
+        let (single, _) = best_move_clearing(&board, Piece::I, 1, &weights)
+            .unwrap_or_else(|| panic!("I completes row 4"));
+        let (tetris, _) = best_move_clearing(&board, Piece::I, 4, &weights)
+            .unwrap_or_else(|| panic!("I fills the well"));
+        assert!(single != tetris);
+        assert_eq!(board.clone().do_move(&single), 1);
+        assert_eq!(board.clone().do_move(&tetris), 4);
+        assert!(best_move_clearing(&board, Piece::I, 2, &weights).is_none());
+    }
+
+    #[test]
+    fn test_root_hold_off_places_current_piece() {
+        let board = board_from_rows(&[FULL_ROW & !(1 << 9); 4]);
+        let config = SearchConfig {
+            beam_width: 200,
+            depth: 1,
+            extend_queue_7bag: false,
+            root_hold: false,
+            ..SearchConfig::default()
+        };
+        let mut state = GameState::new(board, Piece::O, Vec::new());
+        state.hold = Some(Piece::I);
+        let r = find_best_move(&state, &config, &EvalWeights::default())
+            .unwrap_or_else(|| panic!("O fits"));
+        assert!(!r.hold_used);
+        assert_eq!(r.best_move.piece(), Piece::O);
+    }
+
+    #[test]
+    fn test_aggressive_objective_takes_the_tetris() {
+        let well = FULL_ROW & !(1 << 9);
+        let board = board_from_rows(&[well, well, well, well, 0x3F]);
+        let state = GameState::new(board, Piece::I, vec![Piece::O, Piece::T, Piece::L]);
+        let first_attack = |objective| {
+            let config = SearchConfig {
+                beam_width: 200,
+                depth: 2,
+                ..SearchConfig::with_objective(objective)
+            };
+            let r = find_best_move(&state, &config, &EvalWeights::default())
+                .unwrap_or_else(|| panic!("moves exist"));
+            let mut after = state.board.clone();
+            let lines = after.do_move(&r.best_move) as u8;
+            placement_attack(&state, &r.best_move, &after, lines, &config.attack_config)
+        };
+        let aggressive = first_attack(Objective::aggressive());
+        let balanced = first_attack(Objective::balanced());
+        assert!(
+            aggressive > balanced,
+            "aggressive {aggressive} vs balanced {balanced}"
+        );
+    }
+
+    #[test]
+    fn test_survival_probability_near_full_vs_low() {
+        let weights = EvalWeights::default();
+        let pieces = [Piece::T, Piece::O, Piece::S];
+        let stack = |height: usize| {
+            // holes staggered so no row clears without digging
+            let rows: Vec<u16> = (0..height)
+                .map(|y| FULL_ROW & !(1 << (y % 2 * 8 + 1)))
+                .collect();
+            board_from_rows(&rows)
+        };
+        assert!(!survival_probability(&stack(17), 4, &pieces, &weights));
+        // 19 rows after the garbage: one free row, and an O needs two
+        assert!(!survival_probability(&stack(15), 4, &[Piece::O], &weights));
+        assert!(survival_probability(&stack(2), 4, &pieces, &weights));
+        assert!(survival_probability(&Board::new(), 0, &[], &weights));
+    }
+
+    #[test]
+    fn test_reachable_boards_distinct() {
+        let one = reachable_boards(&Board::new(), &[Piece::O], 1);
+        // O lands in one of 9 columns on an empty board
+        assert_eq!(one.len(), 9);
+
+        let two = reachable_boards(&Board::new(), &[Piece::O, Piece::I], 2);
+        assert!(two.len() > one.len());
+        let hashes: std::collections::HashSet<u64> = two.iter().map(Board::content_hash).collect();
+        assert_eq!(hashes.len(), two.len());
+        assert!(two.iter().all(|b| !b.is_empty()));
+        // k beyond the queue stops at the queue
+        assert_eq!(reachable_boards(&Board::new(), &[Piece::O], 3).len(), 9);
+        assert!(reachable_boards(&Board::new(), &[Piece::O], 0).is_empty());
+    }
+
+    #[test]
+    fn test_max_preview_keeps_depth_past_preview() {
+        // Z held, S current and I O T L previewed leave only J in the bag:
+        // the extension supplies it, whatever the untrusted tail says
+        let queue = vec![Piece::I, Piece::O, Piece::T, Piece::L, Piece::S, Piece::Z];
+        let config = SearchConfig {
+            beam_width: 50,
+            depth: 6,
+            max_preview: Some(4),
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        let search = |queue: &[Piece], config: &SearchConfig| {
+            let mut state = GameState::new(Board::new(), Piece::S, queue.to_vec());
+            state.hold = Some(Piece::Z);
+            find_best_move(&state, config, &weights).unwrap_or_else(|| panic!("moves exist"))
+        };
+        let capped = search(&queue, &config);
+        assert_eq!(capped.pv.len(), 6);
+        // same as only ever having seen four previews
+        let seen = search(&queue[..4], &config);
+        assert_eq!(capped.score, seen.score);
+        assert_eq!(capped.best_move.raw(), seen.best_move.raw());
+
+        let no_bag = SearchConfig {
+            extend_queue_7bag: false,
+            ..config
+        };
+        assert_eq!(search(&queue, &no_bag).pv.len(), 5);
+    }
+
+    #[test]
+    fn test_well_plan_follows_next_i() {
+        let board = board_from_rows(&[FULL_ROW & !(1 << 9); 3]);
+
+        let soon = well_plan(&board, &[Piece::O, Piece::I, Piece::T], 9);
+        assert_eq!(
+            soon,
+            WellPlan {
+                keep_well: true,
+                i_distance: Some(1)
+            }
+        );
+
+        use Piece::*;
+        let dry = well_plan(&board, &[O, T, S, Z, L, J, O, T, S, Z], 9);
+        assert!(!dry.keep_well);
+        assert_eq!(dry.i_distance, None);
+
+        let far = well_plan(&board, &[O, T, S, Z, L, J, O, T, I], 9);
+        assert_eq!(far.i_distance, Some(8));
+        assert!(!far.keep_well);
+
+        // a capped well can't take the I anyway
+        let well = FULL_ROW & !(1 << 9);
+        let capped = well_plan(&board_from_rows(&[well, well, well, 1 << 9]), &[I], 9);
+        assert!(!capped.keep_well);
+    }
+
     #[test]
     fn test_hold_none_uses_queue() {
         let state = GameState::new(Board::new(), Piece::T, vec![Piece::I, Piece::O]);
@@ -951,4 +1665,482 @@ mod tests {
             full.position_complexity
         );
     }
+
+    #[test]
+    fn test_placed_cells_after_clear_shifts_down() {
+        let board = board_from_rows(&[FULL_ROW & !(0b111 << 3)]);
+        // T's flat side completes row 0; its nub drops from row 1 to row 0
+        let t = Move::new(Piece::T, Rotation::North, 4, 0, false);
+        assert_eq!(placed_cells_after_clear(&board, &t), (vec![(4, 0)], 0b1));
+
+        // no clear: cells as placed
+        let (mut cells, mask) = placed_cells_after_clear(
+            &Board::new(),
+            &Move::new(Piece::O, Rotation::North, 0, 0, false),
+        );
+        assert_eq!(mask, 0);
+        cells.sort_unstable();
+        assert_eq!(cells, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn test_apply_move_verbose_reports_cleared_rows() {
+        // rows 0 and 1 filled except columns 0-1; an O there clears both
+        let board = board_from_rows(&[FULL_ROW & !0b11, FULL_ROW & !0b11, 1 << 5]);
+        let mv = Move::new(Piece::O, Rotation::North, 0, 0, false);
+
+        let (after, lines, mask) = apply_move_verbose(&board, &mv);
+        assert_eq!(lines, 2);
+        assert_eq!(mask, 0b11);
+        assert_eq!(after.rows[0], 1 << 5);
+        assert_eq!(after.height(), 1);
+
+        let (plain, plain_lines) = apply_move(&board, &mv);
+        assert_eq!(plain_lines, lines);
+        assert_eq!(plain.rows, after.rows);
+    }
+
+    fn tall_well_board() -> Board {
+        // 20 rows filled except x=0 — only a vertical I into the well stays visible
+        board_from_rows(&[FULL_ROW & !1; 20])
+    }
+
+    #[test]
+    fn test_filters_topout_drops_buffer_placements() {
+        let board = tall_well_board();
+        let mut moves = MoveBuffer::new();
+        generate(&board, &mut moves, Piece::I, false);
+        assert!(moves.iter().any(|m| m.tops_out()));
+
+        let safe = filters_topout(moves.as_slice(), Piece::I);
+        assert!(!safe.is_empty());
+        assert!(safe.len() < moves.len());
+        assert!(safe.iter().all(|m| !m.tops_out()));
+        // every surviving placement drops down the x=0 well
+        assert!(safe
+            .iter()
+            .all(|m| m.cells().coords.iter().all(|c| m.x() + c.x as i32 == 0)));
+        assert!(filters_topout(moves.as_slice(), Piece::T).is_empty());
+    }
+
+    #[test]
+    fn test_avoid_topout_search_survives() {
+        let state = GameState::new(tall_well_board(), Piece::I, vec![Piece::O]);
+        let config = SearchConfig {
+            beam_width: 50,
+            depth: 1,
+            avoid_topout: true,
+            ..SearchConfig::default()
+        };
+        let result = find_best_move(&state, &config, &EvalWeights::default())
+            .unwrap_or_else(|| panic!("the well drop should be available"));
+        assert!(!result.best_move.tops_out());
+    }
+
+    #[test]
+    fn test_find_best_move_avoiding_keeps_column_clear() {
+        let mut board = Board::new();
+        board.spawn_garbage(2, 9);
+        let state = GameState::new(board, Piece::L, vec![Piece::T, Piece::S]);
+        let config = SearchConfig {
+            beam_width: 50,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        for avoid in [0, 4, 9] {
+            let (mv, _) = find_best_move_avoiding(&state, &config, &EvalWeights::default(), avoid)
+                .unwrap_or_else(|| panic!("placement avoiding x={avoid} exists"));
+            assert!(!mv.touches_column(avoid), "{mv:?} touches x={avoid}");
+        }
+    }
+
+    #[test]
+    fn test_find_best_move_avoiding_none_when_forced() {
+        // with top-outs excluded, the only placement is the vertical I down x=0
+        let state = GameState::new(tall_well_board(), Piece::I, vec![]);
+        let config = SearchConfig {
+            beam_width: 50,
+            depth: 1,
+            extend_queue_7bag: false,
+            avoid_topout: true,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        assert!(find_best_move_avoiding(&state, &config, &weights, 0).is_none());
+        assert!(find_best_move_avoiding(&state, &config, &weights, 5).is_some());
+    }
+
+    #[test]
+    fn test_play_game_terminates_with_sane_stats() {
+        let config = SearchConfig {
+            beam_width: 30,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+        let stats = play_game_with(&config, &weights, 17, 40);
+        assert!(stats.pieces > 0);
+        assert!(stats.pieces == 40 || stats.topped_out);
+        // every cleared line needs 10 cells, each piece brings 4
+        assert!(stats.lines as usize * 10 <= stats.pieces * 4);
+        assert!(stats.attack >= 0.0);
+
+        assert_eq!(play_game_with(&config, &weights, 17, 40), stats);
+        assert_eq!(play_game(&weights, 17, 0).pieces, 0);
+    }
+
+    fn root_clear_events(state: &GameState, config: &SearchConfig) -> Vec<ClearEvent> {
+        let weights = EvalWeights::default();
+        let mut tt = None;
+        let mut ctx = SearchExpansionContext {
+            config,
+            weights: &weights,
+            remaining_depth: 0,
+            zobrist_keys: get_zobrist_keys(),
+            tt: &mut tt,
+        };
+        expand_root(state, &mut ctx)
+            .into_iter()
+            .flat_map(|n| n.path_clear_events)
+            .collect()
+    }
+
+    #[test]
+    fn test_root_attack_includes_surge_on_b2b_break() {
+        // one line open at x=0..1: an O single breaks a b2b 6 chain
+        let board = board_from_rows(&[FULL_ROW & !0b11]);
+        let mut state = GameState::new(board, Piece::O, vec![]);
+        state.b2b = 6;
+
+        let config = SearchConfig::default();
+        let events = root_clear_events(&state, &config);
+        let single = events
+            .iter()
+            .find(|e| e.lines_cleared == 1)
+            .unwrap_or_else(|| panic!("O single should be generated"));
+        assert!(single.is_surge_release);
+        let single_attack = |b2b_broken_from| {
+            calculate_attack_full(&AttackContext {
+                lines: 1,
+                spin: SpinType::NoSpin,
+                b2b: 0,
+                combo: 1,
+                config: &config.attack_config,
+                is_perfect_clear: false,
+                b2b_broken_from,
+                clears_garbage: false,
+            })
+        };
+        let plain = single_attack(None);
+        assert!(single_attack(Some(6)) > plain);
+        assert_eq!(single.attack_sent, single_attack(Some(6)));
+
+        let uncharged = SearchConfig {
+            attack_config: AttackConfig::builder().charging(false).build(),
+            ..SearchConfig::default()
+        };
+        let events = root_clear_events(&state, &uncharged);
+        let single = events
+            .iter()
+            .find(|e| e.lines_cleared == 1)
+            .unwrap_or_else(|| panic!("O single should be generated"));
+        assert!(!single.is_surge_release);
+        assert_eq!(single.attack_sent, plain);
+    }
+
+    fn cheese_board() -> Board {
+        board_from_rows(&[2u16, 7, 4, 0].map(|hole| FULL_ROW & !(1 << hole)))
+    }
+
+    #[test]
+    fn test_find_downstack_clears_garbage_without_new_holes() {
+        let board = cheese_board();
+        let queue = [Piece::L, Piece::J, Piece::T, Piece::S];
+        let path = find_downstack(&board, Piece::I, &queue)
+            .unwrap_or_else(|| panic!("cheese board has legal moves"));
+        assert!(!path.is_empty());
+
+        let mut after = board.clone();
+        let mut lines = 0;
+        for (m, p) in path.iter().zip(std::iter::once(Piece::I).chain(queue)) {
+            assert_eq!(m.piece(), p);
+            lines += after.do_move(m);
+        }
+        assert!(lines >= 1);
+        assert!(count_holes(&after) <= count_holes(&board));
+    }
+
+    #[test]
+    fn test_explain_choice_change_on_cheese() {
+        let board = cheese_board();
+        let base = EvalWeights::default();
+        assert!(explain_choice_change(&board, Piece::L, &base, &base).is_none());
+
+        // a weight set that loves holes buries the cheese instead
+        let hole_lover = EvalWeights {
+            holes: 20.0,
+            ..EvalWeights::default()
+        };
+        let (under_a, under_b) = explain_choice_change(&board, Piece::L, &base, &hole_lover)
+            .unwrap_or_else(|| panic!("weights should disagree"));
+        assert_ne!(under_a.raw(), under_b.raw());
+        let holes_after = |m: &Move| {
+            let mut b = board.clone();
+            b.do_move(m);
+            count_holes(&b)
+        };
+        assert!(holes_after(&under_b) > holes_after(&under_a));
+    }
+
+    #[test]
+    fn test_mid_combo_search_prefers_continuing_clear() {
+        // x=0..1 open on row 0: the O single keeps the combo alive
+        let board = board_from_rows(&[FULL_ROW & !0b11]);
+        let config = SearchConfig {
+            beam_width: 100,
+            depth: 1,
+            extend_queue_7bag: false,
+            attack_weight: 5.0,
+            ..SearchConfig::default()
+        };
+        let weights = EvalWeights::default();
+
+        let mut mid_combo = GameState::new(board.clone(), Piece::O, vec![]);
+        mid_combo.combo = 4;
+        let full = find_best_move_with_scores(&mid_combo, &config, &weights)
+            .unwrap_or_else(|| panic!("O has moves"));
+        let (_, lines) = apply_move(&board, &full.best.best_move);
+        assert_eq!(lines, 1);
+        assert!(full.attack_score > 0.0);
+
+        // the same single is worth more mid-combo than from a cold start
+        let cold = GameState::new(board.clone(), Piece::O, vec![]);
+        let cold_full = find_best_move_with_scores(&cold, &config, &weights)
+            .unwrap_or_else(|| panic!("O has moves"));
+        let score_of = |scores: &[(Move, f32)], m: &Move| {
+            scores
+                .iter()
+                .find(|(s, _)| s.raw() == m.raw())
+                .map(|(_, v)| *v)
+                .unwrap_or_else(|| panic!("{m:?} missing from root scores"))
+        };
+        let single = full.best.best_move;
+        assert!(score_of(&full.root_scores, &single) > score_of(&cold_full.root_scores, &single));
+    }
+
+    struct HeightLover;
+
+    impl Evaluator for HeightLover {
+        fn score(&self, board: &Board, _lines: u8) -> f32 {
+            board.height() as f32
+        }
+    }
+
+    struct LinesLover;
+
+    impl Evaluator for LinesLover {
+        fn score(&self, _board: &Board, lines: u8) -> f32 {
+            lines as f32
+        }
+    }
+
+    #[test]
+    fn test_tt_keeps_scores_apart_by_lines() {
+        use crate::search_expand::evaluate_with_tt;
+        let mut tt = Some(TranspositionTable::new(1024));
+        let board = board_from_rows(&[0b11]);
+        let keys = get_zobrist_keys();
+
+        assert_eq!(evaluate_with_tt(&board, 0, &LinesLover, 2, keys, &mut tt), 0.0);
+        assert_eq!(evaluate_with_tt(&board, 2, &LinesLover, 2, keys, &mut tt), 2.0);
+        // still cached per clear count
+        assert_eq!(evaluate_with_tt(&board, 0, &LinesLover, 2, keys, &mut tt), 0.0);
+    }
+
+    #[test]
+    fn test_custom_evaluator_changes_choice() {
+        let state = GameState::new(Board::new(), Piece::I, vec![]);
+        let config = SearchConfig {
+            beam_width: 50,
+            depth: 1,
+            extend_queue_7bag: false,
+            attack_weight: 0.0,
+            chain_weight: 0.0,
+            context_weight: 0.0,
+            ..SearchConfig::default()
+        };
+        let height_after = |m: &Move| apply_move(&Board::new(), m).0.height();
+
+        let flat = find_best_move(&state, &config, &EvalWeights::default())
+            .unwrap_or_else(|| panic!("I has moves"));
+        assert_eq!(height_after(&flat.best_move), 1);
+
+        let tall = find_best_move(&state, &config, &HeightLover)
+            .unwrap_or_else(|| panic!("I has moves"));
+        assert_eq!(height_after(&tall.best_move), 4);
+        assert_eq!(tall.score, 4.0);
+    }
+
+    #[test]
+    fn test_diversity_prune_keeps_distinct_shape() {
+        use crate::state::{FatalityState, ObligationState};
+        let node_on = |score: f32, m: Move| {
+            let mut n = make_node(score, FatalityState::Safe, ObligationState::None);
+            n.board.place(&m);
+            n.root_move = m;
+            n
+        };
+        // same flat board reached by two different root moves
+        let flat = node_on(10.0, Move::new(Piece::I, Rotation::North, 1, 0, false));
+        let mut flat_dup = node_on(9.0, Move::new(Piece::I, Rotation::North, 1, 0, false));
+        flat_dup.root_move = Move::new(Piece::I, Rotation::South, 2, 0, false);
+        let tall = node_on(5.0, Move::new(Piece::I, Rotation::East, 9, 2, false));
+        let beam = vec![flat, flat_dup, tall];
+
+        let mut plain = beam.clone();
+        truncate_with_forced(&mut plain, 2, None);
+        assert!(plain.iter().all(|n| n.board.height() == 1));
+
+        let mut diverse = beam.clone();
+        prune_similar(&mut diverse, None);
+        truncate_with_forced(&mut diverse, 2, None);
+        assert_eq!(diverse.len(), 2);
+        assert_eq!(diverse[1].board.height(), 4);
+
+        // a forced duplicate survives pruning
+        let mut forced = beam;
+        prune_similar(&mut forced, Some(Move::new(Piece::I, Rotation::South, 2, 0, false)));
+        assert_eq!(forced.len(), 3);
+    }
+
+    #[test]
+    fn test_best_per_rotation_covers_all_t_rotations() {
+        let best = best_per_rotation(&Board::new(), Piece::T, &EvalWeights::default());
+        for (i, slot) in best.iter().enumerate() {
+            let (m, _) = slot.unwrap_or_else(|| panic!("rotation {i} has no placement"));
+            assert_eq!(m.rotation() as usize, i);
+            assert_eq!(m.piece(), Piece::T);
+        }
+    }
+
+    #[test]
+    fn test_attack_per_line_favours_tetrises() {
+        let config = AttackConfig::tetra_league();
+        let attack_for = |lines: u8, b2b: u8| {
+            calculate_attack_full(&AttackContext {
+                lines,
+                spin: SpinType::NoSpin,
+                b2b,
+                combo: 0,
+                config: &config,
+                is_perfect_clear: false,
+                b2b_broken_from: None,
+                clears_garbage: false,
+            })
+        };
+
+        // same piece count, combo reset between clears
+        let mut tetrises = GameStats::default();
+        let mut singles = GameStats::default();
+        for i in 0..4u8 {
+            tetrises.record(4, attack_for(4, i + 1), 0, i + 1);
+            singles.record(1, attack_for(1, 0), 0, 0);
+        }
+        assert_eq!(tetrises.lines, 16);
+        assert!(tetrises.attack_per_line >= 1.0, "{}", tetrises.attack_per_line);
+        assert!(singles.attack_per_line < 0.5, "{}", singles.attack_per_line);
+        assert_eq!(tetrises.attack_per_line, tetrises.attack / 16.0);
+
+        // no lines: ratio stays zero instead of dividing by zero
+        let mut idle = GameStats::default();
+        idle.record(0, 0.0, 0, 0);
+        assert_eq!(idle.attack_per_line, 0.0);
+    }
+
+    #[test]
+    fn test_difficulty_scaling_changes_hole_count() {
+        let config = SearchConfig {
+            beam_width: 30,
+            depth: 2,
+            ..SearchConfig::default()
+        };
+        let holes = |difficulty: f32| -> i32 {
+            let weights = EvalWeights::scaled(difficulty);
+            (1..=3)
+                .map(|seed| play_game_with(&config, &weights, seed, 40).holes)
+                .sum()
+        };
+        let weak = holes(0.0);
+        let strong = holes(1.0);
+        assert!(weak > strong, "weak {weak} vs strong {strong}");
+    }
+
+    #[test]
+    fn test_search_config_try_new_bounds() {
+        assert!(SearchConfig::try_new(0, 10).is_err());
+        assert!(SearchConfig::try_new(3, 0).is_err());
+        let err = SearchConfig::try_new(MAX_SEARCH_DEPTH + 1, 10).err().unwrap_or_default();
+        assert!(err.contains("with_depth"), "{err}");
+
+        let ok = SearchConfig::try_new(3, 10).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!((ok.depth, ok.beam_width), (3, 10));
+        assert_eq!(SearchConfig::with_depth(MAX_SEARCH_DEPTH + 4, 10).depth, MAX_SEARCH_DEPTH + 4);
+    }
+
+    #[test]
+    fn test_with_depth_searches_past_supported_max() {
+        let queue: Vec<Piece> = (0..24).map(|i| ALL_PIECES[i % ALL_PIECES.len()]).collect();
+        let state = GameState::new(Board::new(), Piece::T, queue);
+        let run = |depth: usize| {
+            let config = SearchConfig {
+                extend_queue_7bag: false,
+                quiescence_max_extensions: 0,
+                ..SearchConfig::with_depth(depth, 8)
+            };
+            find_best_move(&state, &config, &EvalWeights::default())
+                .unwrap_or_else(|| panic!("empty board has moves"))
+                .pv
+                .len()
+        };
+        assert_eq!(run(3), 3);
+        assert_eq!(run(MAX_SEARCH_DEPTH + 4), MAX_SEARCH_DEPTH + 4);
+    }
+
+    #[test]
+    fn test_min_resulting_bumpiness_fills_notch() {
+        // one row across, missing column 4
+        let board = board_from_rows(&[FULL_ROW & !(1 << 4)]);
+        let (m, bump) = min_resulting_bumpiness(&board, Piece::T)
+            .unwrap_or_else(|| panic!("T fits"));
+        let mut after = board.clone();
+        after.do_move(&m);
+        assert!(after.occupied(4, 0), "{m:?} should fill the notch");
+        assert_eq!(bump, surface_bumpiness(&after));
+        assert!(bump <= 2);
+
+        // an I completing the only row leaves an empty, flat board
+        let flat = board_from_rows(&[FULL_ROW & !(0xF << 6)]);
+        let (_, flat_bump) = min_resulting_bumpiness(&flat, Piece::I)
+            .unwrap_or_else(|| panic!("I fits"));
+        assert_eq!(flat_bump, 0);
+    }
+
+    #[test]
+    fn test_max_attack_move_finds_quad() {
+        let mut board = Board::new();
+        for y in 0..4 {
+            board.fill_row_except(y, 9);
+        }
+        let config = AttackConfig::tetra_league();
+        let (m, attack) = max_attack_move(&board, Piece::I, 0, 0, &config)
+            .unwrap_or_else(|| panic!("I fits"));
+        let mut after = board.clone();
+        assert_eq!(after.do_move(&m), 4);
+        assert!(attack > 0.0);
+
+        // nothing clears for O, so every placement sends zero
+        let (_, o_attack) = max_attack_move(&board, Piece::O, 0, 0, &config)
+            .unwrap_or_else(|| panic!("O fits"));
+        assert_eq!(o_attack, 0.0);
+    }
 }
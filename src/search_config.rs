@@ -1,11 +1,12 @@
 use crate::attack::AttackConfig;
 use crate::board::Board;
-use crate::eval::EvalWeights;
+use crate::eval::Evaluator;
 use crate::header::{Move, Piece};
 use crate::state::{ClearEvent, CoachingState, GameState};
 use crate::transposition::{TranspositionTable, ZobristKeys};
 use smallvec::SmallVec;
 
+#[derive(Clone)]
 pub struct SearchConfig {
     pub beam_width: usize,
     pub depth: usize,
@@ -21,6 +22,22 @@ pub struct SearchConfig {
     pub max_depth_factor: f32,
     pub quiescence_max_extensions: usize,
     pub quiescence_beam_fraction: f32,
+    /// Skip placements with any mino in the hidden buffer (see `filters_topout`).
+    pub avoid_topout: bool,
+    /// Root placements may not touch this column (see `find_best_move_avoiding`).
+    pub avoid_column: Option<usize>,
+    /// Before truncating the beam, drop nodes whose board has the same
+    /// `surface_profile` as a higher-ranked node.
+    pub diversity_prune: bool,
+    /// Consider the root hold branch. Off once hold was already pressed this
+    /// turn, so the search only places the current piece.
+    pub root_hold: bool,
+    /// Subtracted per unit of `topout_risk` at every node.
+    pub survival_weight: f32,
+    /// Most queue pieces the search trusts as the visible preview. The rest
+    /// of the queue is dropped before the 7-bag extension, which fills in
+    /// what the bag guarantees past the preview. None reads the whole queue.
+    pub max_preview: Option<usize>,
 }
 
 impl Default for SearchConfig {
@@ -40,12 +57,98 @@ impl Default for SearchConfig {
             max_depth_factor: 2.45,
             quiescence_max_extensions: 3,
             quiescence_beam_fraction: 0.15,
+            avoid_topout: false,
+            avoid_column: None,
+            diversity_prune: false,
+            root_hold: true,
+            survival_weight: 0.0,
+            max_preview: None,
         }
     }
 }
 
+/// Deepest search `SearchConfig::try_new` accepts: node paths stay inline up
+/// to this many moves. Use `with_depth` to go deeper anyway.
+pub const MAX_SEARCH_DEPTH: usize = 16;
+
+impl SearchConfig {
+    /// Checked constructor: errors on a zero depth or beam width, or a depth
+    /// above `MAX_SEARCH_DEPTH`. Everything else is the default.
+    pub fn try_new(depth: usize, beam_width: usize) -> Result<SearchConfig, String> {
+        if depth == 0 {
+            return Err("search depth must be at least 1".to_string());
+        }
+        if beam_width == 0 {
+            return Err("beam width must be at least 1".to_string());
+        }
+        if depth > MAX_SEARCH_DEPTH {
+            return Err(format!(
+                "depth {depth} exceeds supported max {MAX_SEARCH_DEPTH}; use with_depth to force it"
+            ));
+        }
+        Ok(Self::with_depth(depth, beam_width))
+    }
+
+    /// Unchecked counterpart of `try_new`. Depth is still bounded by the
+    /// queue at search time.
+    pub fn with_depth(depth: usize, beam_width: usize) -> SearchConfig {
+        SearchConfig {
+            depth,
+            beam_width,
+            ..SearchConfig::default()
+        }
+    }
+}
+
+/// One-knob offense/defense trade-off for the search: nodes score
+/// `eval_weight * evaluate + attack_weight * attack - survival_weight *
+/// topout_risk` (plus the chain and context terms, which keep their config
+/// weights). Apply with `SearchConfig::with_objective`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Objective {
+    pub eval_weight: f32,
+    pub attack_weight: f32,
+    pub survival_weight: f32,
+}
+
+impl Objective {
+    /// Default search weights with a strong pull away from topping out.
+    pub fn balanced() -> Self {
+        Self {
+            eval_weight: 1.0,
+            attack_weight: 0.5,
+            survival_weight: 10.0,
+        }
+    }
+
+    /// Trades board shape for damage and tolerates a taller stack.
+    pub fn aggressive() -> Self {
+        Self {
+            eval_weight: 0.5,
+            attack_weight: 4.0,
+            survival_weight: 2.0,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// Default config with the board, attack and survival weights taken from
+    /// `objective`.
+    pub fn with_objective(objective: Objective) -> SearchConfig {
+        SearchConfig {
+            board_weight: objective.eval_weight,
+            attack_weight: objective.attack_weight,
+            survival_weight: objective.survival_weight,
+            ..SearchConfig::default()
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct SearchResult {
     pub best_move: Move,
+    /// The root "hold current, play the held/next piece" branch won over
+    /// playing the current piece (see `expand_root`).
     pub hold_used: bool,
     pub score: f32,
     pub pv: Vec<Move>,
@@ -53,6 +156,7 @@ pub struct SearchResult {
     pub pv_clear_events: Vec<ClearEvent>,
 }
 
+#[derive(Clone)]
 pub struct SearchResultFull {
     pub best: SearchResult,
     pub root_scores: Vec<(Move, f32)>,
@@ -68,7 +172,7 @@ pub struct SearchResultFull {
 
 pub(crate) struct SearchExpansionContext<'a> {
     pub config: &'a SearchConfig,
-    pub weights: &'a EvalWeights,
+    pub weights: &'a dyn Evaluator,
     pub remaining_depth: usize,
     pub zobrist_keys: &'a ZobristKeys,
     pub tt: &'a mut Option<TranspositionTable>,
@@ -78,7 +182,7 @@ pub(crate) struct SearchIterationParams<'a> {
     pub state: &'a GameState,
     pub queue: &'a [Piece],
     pub config: &'a SearchConfig,
-    pub weights: &'a EvalWeights,
+    pub weights: &'a dyn Evaluator,
     pub max_depth: usize,
     pub beam_width: usize,
     pub zobrist_keys: &'a ZobristKeys,
@@ -1,7 +1,9 @@
-use crate::analysis::{assemble_composite, shape_chain_value, shape_context_modifier};
+use crate::analysis::{
+    assemble_composite, shape_chain_value, shape_context_modifier, topout_risk,
+};
 use crate::attack::{calculate_attack_full, AttackContext};
 use crate::board::Board;
-use crate::eval::{evaluate, EvalWeights};
+use crate::eval::Evaluator;
 use crate::header::{Move, Piece};
 use crate::move_buffer::MoveBuffer;
 use crate::movegen::generate;
@@ -54,7 +56,13 @@ pub(crate) fn gen_and_eval_root(
     generate(&state.board, &mut moves, piece, true);
 
     for m in moves.as_slice() {
-        if !state.board.legal_lock_placement(m) {
+        if !state.board.legal_lock_placement(m)
+            || (ctx.config.avoid_topout && m.tops_out())
+            || ctx
+                .config
+                .avoid_column
+                .is_some_and(|x| m.touches_column(x))
+        {
             continue;
         }
 
@@ -78,6 +86,7 @@ pub(crate) fn gen_and_eval_root(
 
         let board_eval = evaluate_with_tt(
             &result_board,
+            lines_cleared,
             ctx.weights,
             ctx.remaining_depth,
             ctx.zobrist_keys,
@@ -111,7 +120,8 @@ pub(crate) fn gen_and_eval_root(
                 b2b_after: next_b2b,
                 combo_before: state.combo,
                 combo_after: next_combo,
-                is_surge_release: b2b_broken_from.is_some(),
+                is_surge_release: b2b_broken_from.is_some()
+                    && ctx.config.attack_config.b2b_charging,
                 is_garbage_clear: clears_garbage,
                 is_perfect_clear,
                 piece,
@@ -129,7 +139,8 @@ pub(crate) fn gen_and_eval_root(
             combo_context + coaching_context_bias(state.coaching, coaching),
         );
         let composite_score =
-            assemble_composite(board_eval, attack_val, chain_val, context_mod, ctx.config);
+            assemble_composite(board_eval, attack_val, chain_val, context_mod, ctx.config)
+                - ctx.config.survival_weight * topout_risk(&coaching);
 
         nodes.push(SearchNode {
             board: result_board,
@@ -166,7 +177,9 @@ pub(crate) fn expand_node(
     generate(&parent.board, &mut moves, piece, true);
 
     for m in moves.as_slice() {
-        if !parent.board.legal_lock_placement(m) {
+        if !parent.board.legal_lock_placement(m)
+            || (ctx.config.avoid_topout && m.tops_out())
+        {
             continue;
         }
 
@@ -190,6 +203,7 @@ pub(crate) fn expand_node(
 
         let board_eval = evaluate_with_tt(
             &result_board,
+            lines_cleared,
             ctx.weights,
             ctx.remaining_depth,
             ctx.zobrist_keys,
@@ -223,7 +237,8 @@ pub(crate) fn expand_node(
                 b2b_after: next_b2b,
                 combo_before: parent.combo,
                 combo_after: next_combo,
-                is_surge_release: b2b_broken_from.is_some(),
+                is_surge_release: b2b_broken_from.is_some()
+                    && ctx.config.attack_config.b2b_charging,
                 is_garbage_clear: clears_garbage,
                 is_perfect_clear,
                 piece,
@@ -251,7 +266,7 @@ pub(crate) fn expand_node(
             cum_chain / depth_factor,
             context_mod,
             ctx.config,
-        );
+        ) - ctx.config.survival_weight * topout_risk(&coaching);
 
         let mut path: SmallVec<[Move; 16]> = parent.path.clone();
         path.push(*m);
@@ -281,23 +296,24 @@ pub(crate) fn expand_node(
 
 pub(crate) fn evaluate_with_tt(
     board: &Board,
-    weights: &EvalWeights,
+    lines: u8,
+    weights: &dyn Evaluator,
     remaining_depth: usize,
     zobrist_keys: &ZobristKeys,
     tt: &mut Option<TranspositionTable>,
 ) -> f32 {
     if let Some(table) = tt.as_mut() {
         let depth = remaining_depth.min(u8::MAX as usize) as u8;
-        let hash = zobrist_keys.hash_board(board);
+        let hash = zobrist_keys.hash_placement(board, lines);
 
         if let Some(score) = table.probe(hash, depth) {
             return score;
         }
 
-        let score = evaluate(board, weights);
+        let score = weights.score(board, lines);
         table.store(hash, depth, score);
         return score;
     }
 
-    evaluate(board, weights)
+    weights.score(board, lines)
 }
@@ -1,11 +1,11 @@
 // state.rs -- game state for search with queue support
 // extends board::State with piece queue for beam search
 
-use crate::board::Board;
+use crate::board::{Board, VISIBLE_HEIGHT};
 use crate::default_ruleset::ACTIVE_RULES;
 use crate::gen::SPAWN_COL;
 use crate::header::Piece;
-use crate::header::{Move, SpinType};
+use crate::header::{Move, Rotation, SpinType};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FatalityState {
@@ -265,6 +265,15 @@ fn phase_from_u8(v: u8) -> Option<PhaseState> {
     }
 }
 
+/// why a game ended after a lock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameOverReason {
+    /// next piece overlaps the stack at spawn
+    BlockOut,
+    /// last piece locked entirely above the visible field
+    LockOut,
+}
+
 /// game state carrying everything the search needs
 #[derive(Clone)]
 pub struct GameState {
@@ -302,6 +311,35 @@ impl GameState {
         self.queue.len()
     }
 
+    /// first `n` preview pieces (fewer if the queue is shorter)
+    pub fn peek(&self, n: usize) -> &[Piece] {
+        &self.queue[..n.min(self.queue.len())]
+    }
+
+    /// promote the next preview to `current`; None if the queue is empty
+    pub fn pop_next(&mut self) -> Option<Piece> {
+        if self.queue.is_empty() {
+            return None;
+        }
+        self.current = self.queue.remove(0);
+        Some(self.current)
+    }
+
+    /// state after pressing hold: swaps with the held piece (queue untouched),
+    /// or stashes `current` and pulls the next preview when hold is empty.
+    /// None when hold and queue are both empty.
+    pub fn with_hold_swap(&self) -> Option<GameState> {
+        let mut next = self.clone();
+        next.hold = Some(self.current);
+        match self.hold {
+            Some(held) => next.current = held,
+            None => {
+                next.pop_next()?;
+            }
+        }
+        Some(next)
+    }
+
     pub fn infer_hold_used_for_piece(&self, piece: Piece) -> bool {
         if self.hold == Some(piece) {
             return true;
@@ -331,6 +369,32 @@ impl GameState {
             .any(|(x, y)| board.obstructed(*x, *y) || board.occupied(*x, *y))
     }
 
+    /// Game-over check after `last_move` locked and `self` advanced to the next
+    /// piece. Lock out wins over block out, matching guideline order.
+    pub fn check_game_over(&self, last_move: &Move) -> Option<GameOverReason> {
+        let pc = last_move.cells();
+        let lowest = pc
+            .coords
+            .iter()
+            .map(|c| last_move.y() + c.y as i32)
+            .fold(last_move.y(), i32::min);
+        if lowest >= VISIBLE_HEIGHT as i32 {
+            return Some(GameOverReason::LockOut);
+        }
+
+        let spawn = Move::new(
+            self.current,
+            Rotation::North,
+            SPAWN_COL as i32,
+            ACTIVE_RULES.spawn_row,
+            false,
+        );
+        if self.board.obstructed_move(&spawn) {
+            return Some(GameOverReason::BlockOut);
+        }
+        None
+    }
+
     pub fn next_chain_values(
         current_b2b: u8,
         current_combo: u32,
@@ -403,7 +467,6 @@ impl GameState {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::header::{Move, Rotation};
     use crate::movegen::{generate, MoveBuffer};
 
     #[test]
@@ -539,4 +602,76 @@ mod tests {
         assert_eq!(b2b_after_quad, 4, "b2b increments on quad");
         assert_eq!(combo_after_quad, 5, "combo increments on quad");
     }
+
+    #[test]
+    fn test_check_game_over_lock_out() {
+        let state = GameState::new(Board::new(), Piece::I, vec![]);
+        let high = Move::new(Piece::T, Rotation::North, 4, 21, false);
+        assert_eq!(state.check_game_over(&high), Some(GameOverReason::LockOut));
+
+        // one mino still visible: not a lock out
+        let straddle = Move::new(Piece::I, Rotation::East, 0, 20, false);
+        assert!(straddle.cells().coords.iter().any(|c| c.y < 0));
+        assert_eq!(state.check_game_over(&straddle), None);
+    }
+
+    #[test]
+    fn test_check_game_over_block_out() {
+        let mut rows = [0u16; crate::board::BOARD_HEIGHT];
+        rows[ACTIVE_RULES.spawn_row as usize] = 0b11111 << 2;
+        let board = Board::from(&rows);
+        let state = GameState::new(board, Piece::T, vec![]);
+        let last = Move::new(Piece::O, Rotation::North, 0, 0, false);
+        assert_eq!(state.check_game_over(&last), Some(GameOverReason::BlockOut));
+    }
+
+    #[test]
+    fn test_check_game_over_normal_placement() {
+        let mut board = Board::new();
+        let placed = Move::new(Piece::L, Rotation::North, 3, 0, false);
+        board.do_move(&placed);
+        let state = GameState::new(board, Piece::Z, vec![Piece::S]);
+        assert_eq!(state.check_game_over(&placed), None);
+    }
+
+    #[test]
+    fn test_queue_pop_and_peek() {
+        let mut state = GameState::new(Board::new(), Piece::T, vec![Piece::I, Piece::O]);
+        assert_eq!(state.peek(1), &[Piece::I]);
+        assert_eq!(state.peek(5), &[Piece::I, Piece::O]);
+
+        assert_eq!(state.pop_next(), Some(Piece::I));
+        assert_eq!(state.current, Piece::I);
+        assert_eq!(state.peek(5), &[Piece::O]);
+        assert_eq!(state.pop_next(), Some(Piece::O));
+        assert_eq!(state.pop_next(), None);
+        assert_eq!(state.current, Piece::O);
+        assert!(state.peek(3).is_empty());
+    }
+
+    #[test]
+    fn test_hold_swap_keeps_queue_intact() {
+        let mut state = GameState::new(Board::new(), Piece::T, vec![Piece::I, Piece::O]);
+        state.hold = Some(Piece::S);
+        let swapped = state
+            .with_hold_swap()
+            .unwrap_or_else(|| panic!("occupied hold always swaps"));
+        assert_eq!(swapped.current, Piece::S);
+        assert_eq!(swapped.hold, Some(Piece::T));
+        assert_eq!(swapped.queue, state.queue);
+    }
+
+    #[test]
+    fn test_hold_into_empty_slot_pulls_preview() {
+        let state = GameState::new(Board::new(), Piece::T, vec![Piece::I, Piece::O]);
+        let held = state
+            .with_hold_swap()
+            .unwrap_or_else(|| panic!("queue has a next piece"));
+        assert_eq!(held.current, Piece::I);
+        assert_eq!(held.hold, Some(Piece::T));
+        assert_eq!(held.queue, vec![Piece::O]);
+
+        let empty = GameState::new(Board::new(), Piece::T, vec![]);
+        assert!(empty.with_hold_swap().is_none());
+    }
 }
@@ -3,10 +3,13 @@ use crate::header::COL_NB;
 
 const ZOBRIST_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
 pub(crate) const DEFAULT_TT_SIZE: usize = 65_536;
+// clear counts with their own key; larger counts share the last one
+const LINES_KEY_NB: usize = 8;
 
 #[derive(Clone)]
 pub(crate) struct ZobristKeys {
     keys: [[u64; BOARD_HEIGHT]; COL_NB],
+    lines_keys: [u64; LINES_KEY_NB],
 }
 
 impl ZobristKeys {
@@ -20,7 +23,13 @@ impl ZobristKeys {
             }
         }
 
-        Self { keys }
+        let mut lines_keys = [0u64; LINES_KEY_NB];
+        // no clear keeps the plain board hash
+        for key in lines_keys.iter_mut().skip(1) {
+            *key = rng.next_u64();
+        }
+
+        Self { keys, lines_keys }
     }
 
     pub(crate) fn hash_board(&self, board: &Board) -> u64 {
@@ -37,6 +46,12 @@ impl ZobristKeys {
 
         hash
     }
+
+    /// `hash_board` mixed with the clear count of the placement that produced
+    /// `board`, for caching scores that depend on both.
+    pub(crate) fn hash_placement(&self, board: &Board, lines: u8) -> u64 {
+        self.hash_board(board) ^ self.lines_keys[(lines as usize).min(LINES_KEY_NB - 1)]
+    }
 }
 
 impl Default for ZobristKeys {
@@ -63,14 +78,19 @@ impl SplitMix64 {
     }
 
     fn next_u64(&mut self) -> u64 {
-        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
-        let mut z = self.state;
-        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
-        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
-        z ^ (z >> 31)
+        splitmix64(&mut self.state)
     }
 }
 
+/// One SplitMix64 step over a caller-owned state word.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 #[repr(C, align(16))]
 #[derive(Clone, Copy, Default)]
 pub(crate) struct TTEntry {
@@ -159,6 +179,16 @@ mod tests {
         assert_ne!(keys.hash_board(&board_a), keys.hash_board(&board_b));
     }
 
+    #[test]
+    fn placement_hash_separates_clear_counts() {
+        let keys = ZobristKeys::new();
+        let board = board_with_cells(&[(0, 0), (4, 1)]);
+
+        assert_eq!(keys.hash_placement(&board, 0), keys.hash_board(&board));
+        assert_ne!(keys.hash_placement(&board, 1), keys.hash_placement(&board, 0));
+        assert_ne!(keys.hash_placement(&board, 4), keys.hash_placement(&board, 1));
+    }
+
     #[test]
     fn tt_store_probe_round_trip() {
         let mut tt = TranspositionTable::new(1024);
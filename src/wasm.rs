@@ -9,7 +9,10 @@ use crate::eval::{self, evaluate, EvalWeights};
 use crate::header::*;
 use crate::move_buffer::MoveBuffer;
 use crate::movegen::generate;
-use crate::search::{find_best_move, find_best_move_with_scores_forced, SearchConfig};
+use crate::search::{
+    find_best_move, find_best_move_with_scores_forced, play_game, GameStats, SearchConfig,
+    SearchResult,
+};
 use crate::state::{ClearType, GameState, TransitionObservation};
 use crate::wasm_board::JsBoard;
 use crate::pathfinder;
@@ -64,6 +67,8 @@ impl JsAttackConfig {
                 b2b_chaining,
                 combo_table: ct,
                 garbage_multiplier,
+                b2b_charging: true,
+                garbage_cap: None,
             },
         }
     }
@@ -73,6 +78,31 @@ impl JsAttackConfig {
         self.inner.pc_garbage
     }
 
+    #[wasm_bindgen(setter, js_name = "pcGarbage")]
+    pub fn set_pc_garbage(&mut self, value: u8) {
+        self.inner.pc_garbage = value;
+    }
+
+    #[wasm_bindgen(getter, js_name = "pcB2b")]
+    pub fn pc_b2b(&self) -> u8 {
+        self.inner.pc_b2b
+    }
+
+    #[wasm_bindgen(setter, js_name = "pcB2b")]
+    pub fn set_pc_b2b(&mut self, value: u8) {
+        self.inner.pc_b2b = value;
+    }
+
+    #[wasm_bindgen(getter, js_name = "b2bCharging")]
+    pub fn b2b_charging(&self) -> bool {
+        self.inner.b2b_charging
+    }
+
+    #[wasm_bindgen(setter, js_name = "b2bCharging")]
+    pub fn set_b2b_charging(&mut self, value: bool) {
+        self.inner.b2b_charging = value;
+    }
+
     #[wasm_bindgen(getter, js_name = "garbageMultiplier")]
     pub fn garbage_multiplier(&self) -> f32 {
         self.inner.garbage_multiplier
@@ -98,6 +128,19 @@ pub fn evaluate_board_wasm(board: &JsBoard) -> f32 {
     eval::evaluate(&board.inner, &weights)
 }
 
+/// Per-term breakdown under the same weights as `evaluate_with_weights`;
+/// `total` matches it for the same arguments.
+#[wasm_bindgen(js_name = "evaluate_breakdown")]
+pub fn evaluate_breakdown_wasm(
+    board: &JsBoard,
+    height: f32,
+    holes: f32,
+    bumpiness: f32,
+    wells: f32,
+) -> JsValue {
+    to_js(&breakdown_json(board, &plain_weights(height, holes, bumpiness, wells)))
+}
+
 #[wasm_bindgen(js_name = "evaluate_with_weights")]
 pub fn evaluate_with_weights_wasm(
     board: &JsBoard,
@@ -106,14 +149,44 @@ pub fn evaluate_with_weights_wasm(
     bumpiness: f32,
     wells: f32,
 ) -> f32 {
-    let weights = EvalWeights {
+    eval::evaluate(&board.inner, &plain_weights(height, holes, bumpiness, wells))
+}
+
+// the four weights JS can set; everything else stays at its default
+fn plain_weights(height: f32, holes: f32, bumpiness: f32, wells: f32) -> EvalWeights {
+    EvalWeights {
         height,
         holes,
         bumpiness,
         well_depth: wells,
         ..Default::default()
-    };
-    eval::evaluate(&board.inner, &weights)
+    }
+}
+
+fn breakdown_json(board: &JsBoard, weights: &EvalWeights) -> EvalBreakdownJson {
+    eval_breakdown_to_json(&eval::evaluate_breakdown(&board.inner, weights))
+}
+
+/// Clear-type totals for a replay, from each frame's lines cleared.
+#[wasm_bindgen(js_name = "replay_stats")]
+pub fn replay_stats_wasm(lines_cleared: &[u8]) -> JsValue {
+    to_js(&game_stats_to_json(&replay_stats(lines_cleared)))
+}
+
+fn replay_stats(lines_cleared: &[u8]) -> GameStats {
+    let mut stats = GameStats::default();
+    for &lines in lines_cleared {
+        stats.record_clear(lines);
+    }
+    stats
+}
+
+/// Self-play with default weights (see `search::play_game`); unlike
+/// `replay_stats` its `attack_per_line` is filled in.
+#[wasm_bindgen(js_name = "play_game")]
+pub fn play_game_wasm(seed: u32, max_pieces: u32) -> JsValue {
+    let stats = play_game(&EvalWeights::default(), seed as u64, max_pieces as usize);
+    to_js(&game_stats_to_json(&stats))
 }
 
 #[wasm_bindgen(js_name = "evaluate_position")]
@@ -397,6 +470,29 @@ pub fn evaluate_position_wasm(
     }
 }
 
+// 50ms budget, PC bonuses off: the config every browser-side search uses.
+pub(crate) fn browser_search_config() -> SearchConfig {
+    let mut config = SearchConfig {
+        time_budget_ms: Some(50),
+        ..SearchConfig::default()
+    };
+    config.attack_config.pc_garbage = 0;
+    config.attack_config.pc_b2b = 0;
+    config
+}
+
+pub(crate) fn move_result_json(result: &SearchResult) -> MoveResultJson {
+    MoveResultJson {
+        piece: piece_to_external(result.best_move.piece()),
+        rotation: result.best_move.rotation() as u8,
+        x: result.best_move.x() as i8,
+        y: result.best_move.y() as i8,
+        score: result.score,
+        spin: result.best_move.spin() as u8,
+        hold_used: result.hold_used,
+    }
+}
+
 #[wasm_bindgen(js_name = "find_best_move")]
 pub fn find_best_move_wasm(board: &JsBoard, piece: u8, frame: JsValue) -> JsValue {
     let board_clone = board.inner.clone();
@@ -412,23 +508,10 @@ pub fn find_best_move_wasm(board: &JsBoard, piece: u8, frame: JsValue) -> JsValu
         );
 
         let weights = EvalWeights::default();
-        let mut config = SearchConfig {
-            time_budget_ms: Some(50),
-            ..SearchConfig::default()
-        };
-        config.attack_config.pc_garbage = 0;
-        config.attack_config.pc_b2b = 0;
+        let config = browser_search_config();
 
         let search_result = find_best_move(&state, &config, &weights)?;
-        Some(MoveResultJson {
-            piece: piece_to_external(search_result.best_move.piece()),
-            rotation: search_result.best_move.rotation() as u8,
-            x: search_result.best_move.x() as i8,
-            y: search_result.best_move.y() as i8,
-            score: search_result.score,
-            spin: search_result.best_move.spin() as u8,
-            hold_used: search_result.hold_used,
-        })
+        Some(move_result_json(&search_result))
     }));
 
     match result {
@@ -587,3 +670,40 @@ pub fn simulate_coaching_sequence_wasm(board: &JsBoard, path: JsValue) -> JsValu
         _ => JsValue::NULL,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_stats_json_mirrors_clear_counts() {
+        let json = game_stats_to_json(&replay_stats(&[1, 0, 2, 3, 4]));
+        assert_eq!((json.singles, json.doubles, json.triples), (1, 1, 1));
+        assert_eq!(json.lines, 10);
+        assert_eq!(json.pieces, 5);
+    }
+
+    #[test]
+    fn test_game_stats_json_mirrors_attack_per_line() {
+        let mut stats = GameStats::default();
+        for (lines, attack) in [(1, 0.0), (2, 1.0), (3, 2.0), (4, 4.0)] {
+            stats.record(lines, attack, 0, 0);
+        }
+        assert_eq!(game_stats_to_json(&stats).attack_per_line, 0.7);
+    }
+
+    #[test]
+    fn test_breakdown_total_matches_evaluate_with_weights() {
+        let mut board = JsBoard::new();
+        board.inner.spawn_garbage(3, 4);
+        board.inner.fill_column(0, 6);
+        let (height, holes, bumpiness, wells) = (-1.5, -7.0, -0.25, 0.8);
+
+        let terms = breakdown_json(&board, &plain_weights(height, holes, bumpiness, wells));
+        assert_eq!(
+            terms.total,
+            evaluate_with_weights_wasm(&board, height, holes, bumpiness, wells)
+        );
+        assert_ne!(terms.total, evaluate_board_wasm(&board));
+    }
+}
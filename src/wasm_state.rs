@@ -0,0 +1,177 @@
+// wasm_state.rs -- stateful game wrapper for the browser (board, current, hold, queue)
+
+use wasm_bindgen::prelude::*;
+
+use crate::eval::EvalWeights;
+use crate::move_buffer::MoveBuffer;
+use crate::movegen::generate;
+use crate::search::{find_best_move, SearchResult};
+use crate::state::GameState;
+use crate::wasm::{browser_search_config, move_result_json};
+use crate::wasm_board::JsBoard;
+use crate::wasm_types::*;
+
+#[wasm_bindgen]
+pub struct JsGameState {
+    inner: GameState,
+    // hold was pressed this turn; a second press is illegal
+    hold_used: bool,
+}
+
+#[wasm_bindgen]
+impl JsGameState {
+    /// Returns an error for an unknown `current` id; unknown queue ids are dropped.
+    #[wasm_bindgen(constructor)]
+    pub fn new(board: &JsBoard, current: u8, queue: &[u8]) -> Result<JsGameState, JsValue> {
+        let current = piece_from_external(current)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown piece id {current}")))?;
+        Ok(Self {
+            inner: GameState::new(board.inner.clone(), current, queue_from_external(Some(queue))),
+            hold_used: false,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn board(&self) -> JsBoard {
+        JsBoard {
+            inner: self.inner.board.clone(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn current(&self) -> u8 {
+        piece_to_external(self.inner.current)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hold(&self) -> Option<u8> {
+        self.inner.hold.map(piece_to_external)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn queue(&self) -> Vec<u8> {
+        self.inner.queue.iter().map(|&p| piece_to_external(p)).collect()
+    }
+
+    /// Press hold. False (state unchanged) if hold was already used this
+    /// turn, or hold and queue are both empty.
+    #[wasm_bindgen(js_name = "hold_piece")]
+    pub fn hold_piece(&mut self) -> bool {
+        if self.hold_used {
+            return false;
+        }
+        match self.inner.with_hold_swap() {
+            Some(next) => {
+                self.inner = next;
+                self.hold_used = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Lock the current piece at `rotation`/`x`/`y` (as in `best_move`; call
+    /// `hold_piece` first when it says `hold_used`), spawn the next piece and
+    /// re-arm hold. False (state unchanged) if the placement isn't reachable.
+    /// With an empty queue the played piece stays current.
+    #[wasm_bindgen(js_name = "play")]
+    pub fn play(&mut self, rotation: u8, x: i8, y: i8) -> bool {
+        let mut moves = MoveBuffer::new();
+        generate(&self.inner.board, &mut moves, self.inner.current, false);
+        let Some(mv) = moves
+            .as_slice()
+            .iter()
+            .find(|m| m.rotation() as u8 == rotation && m.x() as i8 == x && m.y() as i8 == y)
+            .copied()
+        else {
+            return false;
+        };
+
+        let mut board = self.inner.board.clone();
+        let lines = board.do_move(&mv) as u8;
+        let blocked = GameState::spawn_envelope_blocked(&board);
+        self.inner
+            .apply_move_transition(&mv, lines, self.hold_used, board.height(), blocked);
+        self.inner.board = board;
+        self.inner.pop_next();
+        self.hold_used = false;
+        true
+    }
+
+    /// Same JSON as `find_best_move`; null when nothing fits. After
+    /// `hold_piece` the search only places the (new) current piece.
+    #[wasm_bindgen(js_name = "best_move")]
+    pub fn best_move(&self) -> JsValue {
+        match self.search() {
+            Some(result) => to_js(&move_result_json(&result)),
+            None => JsValue::NULL,
+        }
+    }
+}
+
+impl JsGameState {
+    fn search(&self) -> Option<SearchResult> {
+        let mut config = browser_search_config();
+        config.root_hold = !self.hold_used;
+        find_best_move(&self.inner, &config, &EvalWeights::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::Piece;
+
+    #[test]
+    fn test_hold_then_best_move_uses_queue_piece() {
+        let board = JsBoard::new();
+        let queue = [piece_to_external(Piece::I), piece_to_external(Piece::O)];
+        let mut state = JsGameState::new(&board, piece_to_external(Piece::T), &queue)
+            .unwrap_or_else(|_| panic!("T is a valid piece"));
+
+        assert!(state.hold_piece());
+        assert_eq!(state.hold(), Some(piece_to_external(Piece::T)));
+        assert_eq!(state.current(), piece_to_external(Piece::I));
+        assert_eq!(state.queue(), vec![piece_to_external(Piece::O)]);
+        assert!(!state.hold_piece(), "second hold in one turn");
+
+        let result = state
+            .search()
+            .unwrap_or_else(|| panic!("I fits on an empty board"));
+        assert!(!result.hold_used);
+        assert_eq!(result.best_move.piece(), Piece::I);
+    }
+
+    #[test]
+    fn test_play_clears_hold_for_next_turn() {
+        let board = JsBoard::new();
+        let queue = [
+            piece_to_external(Piece::I),
+            piece_to_external(Piece::O),
+            piece_to_external(Piece::S),
+        ];
+        let mut state = JsGameState::new(&board, piece_to_external(Piece::T), &queue)
+            .unwrap_or_else(|_| panic!("T is a valid piece"));
+
+        assert!(state.hold_piece());
+        assert!(!state.play(0, -5, 0), "off the board");
+        assert!(!state.hold_piece(), "a rejected play keeps the turn");
+
+        let mv = state
+            .search()
+            .unwrap_or_else(|| panic!("I fits on an empty board"))
+            .best_move;
+        assert!(state.play(mv.rotation() as u8, mv.x() as i8, mv.y() as i8));
+        assert_eq!(state.current(), piece_to_external(Piece::O));
+        assert_eq!(state.queue(), vec![piece_to_external(Piece::S)]);
+        assert_eq!(state.board().inner.rows, {
+            let mut b = board.inner.clone();
+            b.do_move(&mv);
+            b.rows
+        });
+
+        assert!(state.hold_piece(), "hold is available again after play");
+        assert_eq!(state.current(), piece_to_external(Piece::T));
+        assert_eq!(state.hold(), Some(piece_to_external(Piece::O)));
+    }
+}
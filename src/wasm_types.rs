@@ -4,7 +4,9 @@
 use wasm_bindgen::prelude::*;
 
 use crate::board::Board;
+use crate::eval::EvalBreakdown;
 use crate::header::*;
+use crate::search::GameStats;
 use crate::state::{
     ClearEvent, CoachingState, FatalityState, GameState, ObligationState, PhaseState, SurgeState,
 };
@@ -203,6 +205,82 @@ pub(crate) struct ReplayFrameContextJson {
 }
 
 
+#[derive(serde::Serialize)]
+pub(crate) struct EvalBreakdownJson {
+    pub holes: f32,
+    pub cell_coveredness: f32,
+    pub height: f32,
+    pub height_upper_half: f32,
+    pub height_upper_quarter: f32,
+    pub bumpiness: f32,
+    pub bumpiness_sq: f32,
+    pub row_transitions: f32,
+    pub well_depth: f32,
+    pub tsd_overhang: f32,
+    pub four_wide_well: f32,
+    pub tetris_ready: f32,
+    pub spin_potential: f32,
+    pub garbage_alignment: f32,
+    pub pillar: f32,
+    pub stack_volume: f32,
+    pub total: f32,
+}
+
+pub(crate) fn eval_breakdown_to_json(terms: &EvalBreakdown) -> EvalBreakdownJson {
+    EvalBreakdownJson {
+        holes: terms.holes,
+        cell_coveredness: terms.cell_coveredness,
+        height: terms.height,
+        height_upper_half: terms.height_upper_half,
+        height_upper_quarter: terms.height_upper_quarter,
+        bumpiness: terms.bumpiness,
+        bumpiness_sq: terms.bumpiness_sq,
+        row_transitions: terms.row_transitions,
+        well_depth: terms.well_depth,
+        tsd_overhang: terms.tsd_overhang,
+        four_wide_well: terms.four_wide_well,
+        tetris_ready: terms.tetris_ready,
+        spin_potential: terms.spin_potential,
+        garbage_alignment: terms.garbage_alignment,
+        pillar: terms.pillar,
+        stack_volume: terms.stack_volume,
+        total: terms.total(),
+    }
+}
+
+#[derive(serde::Serialize)]
+pub(crate) struct GameStatsJson {
+    pub pieces: usize,
+    pub lines: u32,
+    pub singles: u32,
+    pub doubles: u32,
+    pub triples: u32,
+    pub attack: f32,
+    pub max_combo: u32,
+    pub max_b2b: u8,
+    pub topped_out: bool,
+    pub holes: i32,
+    pub attack_per_line: f32,
+    pub finesse_faults: u32,
+}
+
+pub(crate) fn game_stats_to_json(stats: &GameStats) -> GameStatsJson {
+    GameStatsJson {
+        pieces: stats.pieces,
+        lines: stats.lines,
+        singles: stats.singles,
+        doubles: stats.doubles,
+        triples: stats.triples,
+        attack: stats.attack,
+        max_combo: stats.max_combo,
+        max_b2b: stats.max_b2b,
+        topped_out: stats.topped_out,
+        holes: stats.holes,
+        attack_per_line: stats.attack_per_line,
+        finesse_faults: stats.finesse_faults,
+    }
+}
+
 // attack tracking types
 
 pub(crate) fn spin_type_to_str(s: SpinType) -> &'static str {